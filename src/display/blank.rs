@@ -5,23 +5,24 @@ use std::fmt;
 use log::debug;
 use termion::{color, cursor};
 
-use super::{geometry, Widget};
+use super::{geometry, theme::Theme, Widget};
 
 #[derive(Debug)]
-pub struct BlankWidget {
+pub struct BlankWidget<'a> {
     pub bounds: geometry::Rect<u16>,
+    pub theme: &'a Theme,
 }
 
-impl Widget for BlankWidget {
+impl<'a> Widget for BlankWidget<'a> {
     fn bounds(&self) -> geometry::Rect<u16> {
         self.bounds
     }
 }
 
-impl fmt::Display for BlankWidget {
+impl<'a> fmt::Display for BlankWidget<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         let goto = geometry::goto(self.bounds.origin);
-        let color = color::Fg(color::Reset);
+        let color = self.theme.fg(color::Reset);
         write!(fmt, "{}{}", goto, color)?;
 
         let width = self.bounds.size.width;
@@ -1,15 +1,14 @@
 use std::fmt;
 use termion::{color, cursor};
 
-use crate::{
-    model::{Card, Color},
-    utils::format_str::FormattedString,
-};
+use crate::{model::Card, utils::format_str::FormattedString};
 
 use super::{
     blank::BlankWidget,
     frame::{FrameStyle, FrameWidget, Title},
-    geometry, Widget,
+    geometry,
+    theme::Theme,
+    Widget,
 };
 
 lazy_static! {
@@ -30,18 +29,58 @@ pub static CARD_FRAME_STYLE: FrameStyle = FrameStyle {
     title_right: "╶",
 };
 
-impl color::Color for Color {
-    fn write_fg(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Color::Black => color::Reset.write_fg(fmt),
-            Color::Red => color::Red.write_fg(fmt),
-        }
-    }
+/// Dashed, to outline where a card would be rather than an actual card.
+pub static EMPTY_SLOT_FRAME_STYLE: FrameStyle = FrameStyle {
+    top_left: "╭",
+    top: "┄",
+    top_right: "╮",
+    left: "┆",
+    right: "┆",
+    bottom_left: "╰",
+    bottom: "┄",
+    bottom_right: "╯",
+    title_left: "╴",
+    title_right: "╶",
+};
 
-    fn write_bg(&self, _fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        Ok(())
-    }
-}
+pub static SQUARE_CARD_FRAME_STYLE: FrameStyle = FrameStyle {
+    top_left: "┌",
+    top: "─",
+    top_right: "┐",
+    left: "│",
+    right: "│",
+    bottom_left: "└",
+    bottom: "─",
+    bottom_right: "┘",
+    title_left: "╴",
+    title_right: "╶",
+};
+
+pub static ASCII_CARD_FRAME_STYLE: FrameStyle = FrameStyle {
+    top_left: "+",
+    top: "-",
+    top_right: "+",
+    left: "|",
+    right: "|",
+    bottom_left: "+",
+    bottom: "-",
+    bottom_right: "+",
+    title_left: "-",
+    title_right: "-",
+};
+
+pub static ASCII_EMPTY_SLOT_FRAME_STYLE: FrameStyle = FrameStyle {
+    top_left: ".",
+    top: ".",
+    top_right: ".",
+    left: ":",
+    right: ":",
+    bottom_left: "'",
+    bottom: ".",
+    bottom_right: "'",
+    title_left: "-",
+    title_right: "-",
+};
 
 #[derive(Copy, Clone, Debug)]
 pub enum CardWidgetMode {
@@ -56,6 +95,7 @@ pub struct CardWidget<'a> {
     pub card: &'a Card,
     pub origin: geometry::Point2D<u16>,
     pub mode: CardWidgetMode,
+    pub theme: &'a Theme,
 }
 
 impl<'a> CardWidget<'a> {
@@ -69,6 +109,7 @@ impl<'a> CardWidget<'a> {
         if bounds.size.height > 2 && bounds.size.width > 2 {
             let blank = BlankWidget {
                 bounds: bounds.inner_rect(geometry::SideOffsets2D::new_all_same(1)),
+                theme: self.theme,
             };
             write!(fmt, "{}", blank)?;
         }
@@ -77,7 +118,8 @@ impl<'a> CardWidget<'a> {
             bounds,
             top_title: title.map(Title::right),
             bottom_title: None,
-            frame_style: &CARD_FRAME_STYLE,
+            frame_style: self.theme.card_frame_style(),
+            theme: self.theme,
         };
 
         write!(fmt, "{}", frame)?;
@@ -97,12 +139,12 @@ impl<'a> fmt::Display for CardWidget<'a> {
             CardWidgetMode::FullFaceUp => {
                 let interior_coords = self.origin + geometry::vec2(2, 1);
 
-                let color = color::Fg(self.card.color());
+                let color = self.theme.fg(self.theme.suit_color(self.card.suit));
                 let start = geometry::goto(interior_coords);
                 let next = format!("{}{}", cursor::Left(4), cursor::Down(1));
 
                 let rank_str = format!("{}", self.card.rank);
-                let suit_str = format!("{}", self.card.suit);
+                let suit_str = self.theme.suit_symbol(self.card.suit);
 
                 let offset = cursor::Right(3 - rank_str.len() as u16);
 
@@ -118,20 +160,31 @@ impl<'a> fmt::Display for CardWidget<'a> {
                 let start = geometry::goto(interior_coords);
                 let next = format!("{}{}", cursor::Left(4), cursor::Down(1));
 
+                let back = self.theme.card_back();
+
                 self.fmt_frame(None, fmt)?;
-                write!(fmt, "{}{}", start, color::Fg(color::LightBlue))?;
-                write!(fmt, "░░░░{}", next)?;
-                write!(fmt, "░░░░{}", next)?;
+                write!(
+                    fmt,
+                    "{}{}",
+                    start,
+                    self.theme.fg(self.theme.style.card_back.color)
+                )?;
+                write!(fmt, "{}{}", back, next)?;
+                write!(fmt, "{}{}", back, next)?;
             }
 
             CardWidgetMode::SliceFaceUp => {
-                let color = color::Fg(self.card.color());
-                let white = color::Fg(color::White);
+                let color = self.theme.fg(self.theme.suit_color(self.card.suit));
+                let white = self.theme.fg(color::White);
 
                 let rank_str = format!("{}", self.card.rank);
-                let suit_str = format!("{}", self.card.suit);
+                let suit_str = self.theme.suit_symbol(self.card.suit);
 
-                let spacer = if rank_str.len() == 2 { " " } else { "╶╴" };
+                let spacer = if rank_str.len() == 2 {
+                    " "
+                } else {
+                    self.theme.unicode_or("╶╴", "  ")
+                };
 
                 let title = FormattedString::new_with_formatting(color)
                     .push_content(rank_str)
@@ -144,9 +197,9 @@ impl<'a> fmt::Display for CardWidget<'a> {
             }
 
             CardWidgetMode::SliceFaceDown(count) => {
-                let gray = color::Fg(color::LightBlack);
+                let gray = self.theme.fg(color::LightBlack);
 
-                let formatted_count = format!("{}×", count);
+                let formatted_count = format!("{}{}", count, self.theme.unicode_or("×", "x"));
 
                 let title =
                     FormattedString::new_with_formatting(gray).push_content(formatted_count);
@@ -158,3 +211,48 @@ impl<'a> fmt::Display for CardWidget<'a> {
         Ok(())
     }
 }
+
+/// Marks a spot that's empty for good, e.g., a stock that can't be restocked.
+#[derive(Debug)]
+pub struct EmptySlotWidget<'a> {
+    pub origin: geometry::Point2D<u16>,
+    pub theme: &'a Theme,
+}
+
+impl<'a> Widget for EmptySlotWidget<'a> {
+    fn bounds(&self) -> geometry::Rect<u16> {
+        geometry::Rect::new(self.origin, *CARD_SIZE)
+    }
+}
+
+impl<'a> fmt::Display for EmptySlotWidget<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bounds = self.bounds();
+
+        let blank = BlankWidget {
+            bounds: bounds.inner_rect(geometry::SideOffsets2D::new_all_same(1)),
+            theme: self.theme,
+        };
+        let frame = FrameWidget {
+            bounds,
+            top_title: None,
+            bottom_title: None,
+            frame_style: self
+                .theme
+                .unicode_or(&EMPTY_SLOT_FRAME_STYLE, &ASCII_EMPTY_SLOT_FRAME_STYLE),
+            theme: self.theme,
+        };
+        write!(fmt, "{}{}", blank, frame)?;
+
+        let center = self.origin + geometry::vec2(CARD_SIZE.width / 2 - 1, CARD_SIZE.height / 2);
+        write!(
+            fmt,
+            "{}{}{}",
+            geometry::goto(center),
+            self.theme.fg(color::LightBlack),
+            self.theme.unicode_or("✕", "X"),
+        )?;
+
+        Ok(())
+    }
+}
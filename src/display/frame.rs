@@ -3,7 +3,7 @@ use termion::{color, cursor};
 
 use crate::utils::format_str::FormattedString;
 
-use super::{geometry, Widget};
+use super::{geometry, theme::Theme, Widget};
 
 #[derive(Debug)]
 pub struct FrameStyle {
@@ -45,6 +45,32 @@ pub static DOUBLE: FrameStyle = FrameStyle {
     title_right: " ╞",
 };
 
+pub static ASCII_SINGLE: FrameStyle = FrameStyle {
+    top_left: "+-",
+    top: "-",
+    top_right: "-+",
+    left: "|",
+    right: "|",
+    bottom_left: "+-",
+    bottom: "-",
+    bottom_right: "-+",
+    title_left: "[ ",
+    title_right: " ]",
+};
+
+pub static ASCII_DOUBLE: FrameStyle = FrameStyle {
+    top_left: "+=",
+    top: "=",
+    top_right: "=+",
+    left: "|",
+    right: "|",
+    bottom_left: "+=",
+    bottom: "=",
+    bottom_right: "=+",
+    title_left: "[ ",
+    title_right: " ]",
+};
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Direction {
     Left,
@@ -84,6 +110,7 @@ pub struct FrameWidget<'a> {
     pub top_title: Option<Title>,
     pub bottom_title: Option<Title>,
     pub frame_style: &'a FrameStyle,
+    pub theme: &'a Theme,
 }
 
 impl<'a> Widget for FrameWidget<'a> {
@@ -121,6 +148,7 @@ impl<'a> fmt::Display for FrameWidget<'a> {
                 self.frame_style.top,
                 self.frame_style.title_left,
                 self.frame_style.title_right,
+                self.theme,
             )
         } else {
             self.frame_style
@@ -136,6 +164,7 @@ impl<'a> fmt::Display for FrameWidget<'a> {
                 self.frame_style.bottom,
                 self.frame_style.title_left,
                 self.frame_style.title_right,
+                self.theme,
             )
         } else {
             self.frame_style
@@ -146,7 +175,7 @@ impl<'a> fmt::Display for FrameWidget<'a> {
 
         let goto = geometry::goto(self.bounds.origin);
         let step = format!("{}{}", cursor::Down(1), cursor::Left(width));
-        let white = color::Fg(color::White);
+        let white = self.theme.fg(color::White);
 
         write!(
             fmt,
@@ -191,8 +220,9 @@ fn format_with_title(
     filler: &str,
     title_left: &str,
     title_right: &str,
+    theme: &Theme,
 ) -> String {
-    let white = color::Fg(color::White);
+    let white = theme.fg(color::White);
     let formatted_title = FormattedString::new_with_content(title_left)
         .push_formatted_content(text)
         .push_formatting(white)
@@ -1,13 +1,17 @@
 use std::{cell::RefCell, collections::HashMap, convert::TryFrom, fmt};
 
 use log::info;
-use termion::clear;
+use termion::{clear, color};
 
-use crate::model::{AreaId, Game, Suit};
+use crate::{
+    model::{card::SUIT_LEN, keys::Keymap, stats::Statistics, AreaId, Game},
+    utils::str::CharacterLength,
+};
 
 use super::{
     blank::BlankWidget, card::CARD_SIZE, geometry, help::HelpWidget, stack::StackWidget,
-    win::WinWidget, DisplayState, Widget,
+    stats::StatsWidget, status::StatusBarWidget, theme::Theme, win::WinWidget, DisplayState,
+    Widget,
 };
 
 lazy_static! {
@@ -35,6 +39,16 @@ pub struct GameWidget<'a> {
     pub area_ids: &'a [AreaId],
     pub bounds: geometry::Rect<u16>,
     pub game: &'a Game,
+    /// The Vegas bankroll, including the game in progress, if playing Vegas
+    pub bankroll: Option<i64>,
+    pub statistics: &'a Statistics,
+    /// The keys for playing, to list in the help
+    pub keymap: Option<&'a Keymap>,
+    /// The keys for the win message
+    pub win_keymap: Option<&'a Keymap>,
+    /// How many rows the help is scrolled down
+    pub help_scroll: usize,
+    pub theme: &'a Theme,
     pub display_state: DisplayState,
     pub widget_state: &'a GameWidgetState,
 }
@@ -49,6 +63,9 @@ impl<'a> fmt::Display for GameWidget<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         let full_refresh_required = self.is_full_refresh_required();
 
+        // Everything is drawn over the theme's background, including whatever gets cleared.
+        write!(fmt, "{}", self.theme.bg(self.theme.style.background))?;
+
         if full_refresh_required {
             // We're going to clear the whole terminal, so no need to remember where the widgets were.
             self.widget_state.cell.borrow_mut().bounds_cache.clear();
@@ -70,10 +87,16 @@ impl<'a> fmt::Display for GameWidget<'a> {
                 for area_id in area_ids {
                     self.write_area(*area_id, fmt)?;
                 }
+
+                self.write_message_line(fmt)?;
+                self.write_status_bar(fmt)?;
             }
             DisplayState::HelpMessageOpen => {
                 self.write_help(fmt)?;
             }
+            DisplayState::StatisticsOpen => {
+                self.write_stats(fmt)?;
+            }
             DisplayState::WinMessageOpen => {
                 self.write_win(fmt)?;
             }
@@ -113,8 +136,15 @@ impl<'a> GameWidget<'a> {
         }
     }
 
+    /// The part of our bounds where the areas are laid out. The bottom two rows are reserved for
+    /// messages and the status bar.
+    fn board_bounds(&self) -> geometry::Rect<u16> {
+        self.bounds
+            .inner_rect(geometry::SideOffsets2D::new(0, 0, 2, 0))
+    }
+
     fn write_area(&self, area_id: AreaId, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let bounds = bounds_for_area(area_id, self.bounds);
+        let bounds = self.layout().bounds_for_area(area_id, self.board_bounds());
 
         info!("Printing {:?} at {:?}", area_id, bounds.origin);
 
@@ -124,7 +154,10 @@ impl<'a> GameWidget<'a> {
         let bounds_cache = &mut self.widget_state.cell.borrow_mut().bounds_cache;
 
         if let Some(&bounds) = bounds_cache.get(&area_id) {
-            let blank_widget = BlankWidget { bounds };
+            let blank_widget = BlankWidget {
+                bounds,
+                theme: self.theme,
+            };
             write!(fmt, "{}", blank_widget)?;
         }
 
@@ -132,6 +165,9 @@ impl<'a> GameWidget<'a> {
             let stack_widget = StackWidget {
                 bounds,
                 stack: &stack,
+                hint_len: self.hint_len(area_id),
+                exhausted: self.game.passes_remaining(area_id) == Some(0),
+                theme: self.theme,
             };
 
             let new_bounds = stack_widget.bounds();
@@ -146,9 +182,94 @@ impl<'a> GameWidget<'a> {
         Ok(())
     }
 
+    fn layout(&self) -> Layout {
+        let area_ids = self.game.area_ids();
+        let count = |is_kind: fn(&AreaId) -> bool| {
+            u8::try_from(area_ids.iter().filter(|area_id| is_kind(area_id)).count())
+                .unwrap_or(u8::MAX)
+        };
+
+        Layout {
+            tableaux_len: count(|area_id| matches!(area_id, AreaId::Tableaux(_))),
+            foundation_len: count(|area_id| matches!(area_id, AreaId::Foundation(_))),
+            open_stock: self.game.is_open_deck(),
+        }
+    }
+
+    /// How many cards of an area to point out for the current hint. The cards to move are pointed
+    /// out on the source area, and just the top card on the target area.
+    fn hint_len(&self, area_id: AreaId) -> Option<usize> {
+        self.game.hint.and_then(|hint| {
+            if area_id == hint.source {
+                Some(hint.len.max(1))
+            } else if area_id == hint.target {
+                Some(1)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Tell the player why their last action didn't happen, if it didn't. Whatever isn't written is
+    /// blanked out, so the message goes away after the next action.
+    fn write_message_line(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let y = self.bounds.max_y().saturating_sub(2);
+        let goto = geometry::goto(geometry::point2(2, y));
+        let line_len = usize::from(self.bounds.size.width.saturating_sub(4));
+
+        let message = self
+            .theme
+            .suits_in(self.game.message.as_deref().unwrap_or_default());
+        let message: String = message.chars().take(line_len).collect();
+        let len = message.char_len();
+
+        write!(fmt, "{}{}{}", goto, self.theme.fg(color::LightRed), message)?;
+        write!(fmt, "{}", " ".repeat(line_len.saturating_sub(len)))?;
+
+        Ok(())
+    }
+
+    fn write_status_bar(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let y = self.bounds.max_y().saturating_sub(1);
+        let bounds = geometry::Rect::new(
+            geometry::point2(2, y),
+            geometry::size2(self.bounds.size.width.saturating_sub(4), 1),
+        );
+
+        let widget = StatusBarWidget {
+            bounds,
+            game: self.game,
+            bankroll: self.bankroll,
+            keymap: self.keymap,
+            theme: self.theme,
+        };
+
+        write!(fmt, "{}", widget)?;
+
+        Ok(())
+    }
+
     fn write_help(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let widget = HelpWidget {
+        if let Some(keymap) = self.keymap {
+            let widget = HelpWidget {
+                bounds: self.bounds,
+                keymap,
+                area_ids: &self.game.area_ids(),
+                scroll: self.help_scroll,
+                theme: self.theme,
+            };
+
+            write!(fmt, "{}", widget)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_stats(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let widget = StatsWidget {
             bounds: self.bounds,
+            statistics: self.statistics,
+            theme: self.theme,
         };
 
         write!(fmt, "{}", widget)?;
@@ -157,8 +278,11 @@ impl<'a> GameWidget<'a> {
     }
 
     fn write_win(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let default_keymap = Keymap::default();
         let widget = WinWidget {
             bounds: self.bounds,
+            keymap: self.win_keymap.unwrap_or(&default_keymap),
+            theme: self.theme,
         };
 
         write!(fmt, "{}", widget)?;
@@ -167,58 +291,104 @@ impl<'a> GameWidget<'a> {
     }
 }
 
-fn bounds_for_area(area_id: AreaId, widget_bounds: geometry::Rect<u16>) -> geometry::Rect<u16> {
-    let top_left = coords_for_area(area_id);
+/// Where each area goes on the board, which depends on how many tableaux there are and whether the
+/// stock is spread out face up.
+#[derive(Copy, Clone, Debug)]
+struct Layout {
+    tableaux_len: u8,
+    foundation_len: u8,
+    /// An open stock gets a row of its own across the top of the board, and everything else moves
+    /// down to make room.
+    open_stock: bool,
+}
 
-    match area_id {
-        AreaId::Stock => {
-            let right = coords_for_area(AreaId::Talon).x - 1;
-            let bottom = coords_for_area(AreaId::Tableaux(0)).y - 1;
-            let bottom_right = geometry::point2(right, bottom);
+impl Layout {
+    fn bounds_for_area(
+        &self,
+        area_id: AreaId,
+        widget_bounds: geometry::Rect<u16>,
+    ) -> geometry::Rect<u16> {
+        let top_left = self.coords_for_area(area_id);
+
+        match area_id {
+            AreaId::Stock => {
+                let (right, bottom) = if self.open_stock {
+                    let bottom = self.coords_for_area(AreaId::Talon).y - 1;
+                    (widget_bounds.max_x(), bottom)
+                } else {
+                    let right = self.coords_for_area(AreaId::Talon).x - 1;
+                    let bottom = self.coords_for_area(AreaId::Tableaux(0)).y - 1;
+                    (right, bottom)
+                };
+                let bottom_right = geometry::point2(right, bottom);
 
-            geometry::Box2D::new(top_left, bottom_right).to_rect()
-        }
-        AreaId::Talon => {
-            let first_suit = Suit::try_from(0).unwrap();
-            let right = coords_for_area(AreaId::Foundation(first_suit)).x - 1;
-            let bottom = coords_for_area(AreaId::Tableaux(0)).y - 1;
-            let bottom_right = geometry::point2(right, bottom);
+                geometry::Box2D::new(top_left, bottom_right).to_rect()
+            }
+            AreaId::Talon => {
+                let right = self.coords_for_area(AreaId::Foundation(0)).x - 1;
+                let bottom = self.coords_for_area(AreaId::Tableaux(0)).y - 1;
+                let bottom_right = geometry::point2(right, bottom);
 
-            geometry::Box2D::new(top_left, bottom_right).to_rect()
-        }
-        AreaId::Foundation(suit) => {
-            let next_suit = Suit::try_from(u8::from(suit) + 1).ok();
-            let right = if let Some(next_suit) = next_suit {
-                coords_for_area(AreaId::Foundation(next_suit)).x - 1
-            } else {
-                widget_bounds.max_x()
-            };
-            let bottom = coords_for_area(AreaId::Tableaux(0)).y - 1;
-            let bottom_right = geometry::point2(right, bottom);
+                geometry::Box2D::new(top_left, bottom_right).to_rect()
+            }
+            AreaId::Foundation(index) => {
+                let right = if index + 1 < self.foundation_len {
+                    self.coords_for_area(AreaId::Foundation(index + 1)).x - 1
+                } else {
+                    widget_bounds.max_x()
+                };
+                let bottom = self.coords_for_area(AreaId::Tableaux(0)).y - 1;
+                let bottom_right = geometry::point2(right, bottom);
 
-            geometry::Box2D::new(top_left, bottom_right).to_rect()
-        }
-        AreaId::Tableaux(index) => {
-            let right = coords_for_area(AreaId::Tableaux(index + 1)).x - 1;
-            let bottom = widget_bounds.max_y();
-            let bottom_right = geometry::point2(right, bottom);
+                geometry::Box2D::new(top_left, bottom_right).to_rect()
+            }
+            AreaId::Tableaux(index) => {
+                let right = self.coords_for_area(AreaId::Tableaux(index + 1)).x - 1;
+                let bottom = widget_bounds.max_y();
+                let bottom_right = geometry::point2(right, bottom);
 
-            geometry::Box2D::new(top_left, bottom_right).to_rect()
+                geometry::Box2D::new(top_left, bottom_right).to_rect()
+            }
         }
     }
-}
 
-fn coords_for_area(area_id: AreaId) -> geometry::Point2D<u16> {
-    let card_offset = geometry::vec2(CARD_SIZE.width, 0);
+    /// The foundations sit above the last tableaux, so that the board lines up on the right. With
+    /// only a few tableaux that would crowd the talon, so four foundations never move further left
+    /// than they are for the standard seven. Two decks' worth of foundations can't fit beside a
+    /// full talon, so they may crowd it down to a single card.
+    fn coords_for_area(&self, area_id: AreaId) -> geometry::Point2D<u16> {
+        let card_offset = geometry::vec2(CARD_SIZE.width, 0);
+        let column_offset = card_offset + *COLUMN_OFFSET;
+
+        // With an open stock, the talon takes the stock's usual place, one row down.
+        let row_offset = if self.open_stock {
+            geometry::vec2(0, TABLEAUX_COORDS.y)
+        } else {
+            geometry::vec2(0, 0)
+        };
 
-    match area_id {
-        AreaId::Stock => *STOCK_COORDS,
-        AreaId::Talon => *TALON_COORDS,
-        AreaId::Foundation(suit) => {
-            *FOUNDATION_COORDS + (card_offset + *COLUMN_OFFSET) * u16::from(u8::from(suit))
-        }
-        AreaId::Tableaux(index) => {
-            *TABLEAUX_COORDS + (card_offset + *COLUMN_OFFSET) * u16::from(index)
+        match area_id {
+            AreaId::Stock => *STOCK_COORDS,
+            AreaId::Talon if self.open_stock => *STOCK_COORDS + row_offset,
+            AreaId::Talon => *TALON_COORDS,
+            AreaId::Foundation(index) => {
+                let first_aligned_index = self.tableaux_len.saturating_sub(self.foundation_len);
+                let aligned_x =
+                    TABLEAUX_COORDS.x + column_offset.x * u16::from(first_aligned_index);
+
+                let min_x = if self.foundation_len <= SUIT_LEN {
+                    FOUNDATION_COORDS.x
+                } else {
+                    TALON_COORDS.x + column_offset.x
+                };
+
+                let origin = geometry::point2(aligned_x.max(min_x), FOUNDATION_COORDS.y);
+
+                origin + column_offset * u16::from(index) + row_offset
+            }
+            AreaId::Tableaux(index) => {
+                *TABLEAUX_COORDS + column_offset * u16::from(index) + row_offset
+            }
         }
     }
 }
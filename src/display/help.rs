@@ -1,172 +1,396 @@
 use itertools::Itertools;
 use std::{convert::TryFrom, fmt};
-use termion::color;
+use termion::{color, event::Key};
 
-use crate::utils::{format_str::FormattedString, str::CharacterLength};
+use crate::{
+    model::{
+        game::Action,
+        keys::{Binding, Command, Keymap},
+        AreaId,
+    },
+    utils::{format_str::FormattedString, str::CharacterLength},
+};
 
 use super::{
-    frame::{self, FrameWidget, Title},
-    geometry, Widget,
+    blank::BlankWidget,
+    frame::{FrameWidget, Title},
+    geometry,
+    theme::Theme,
+    Widget,
 };
 
+/// How much space to leave between columns
+static COLUMN_GAP: u16 = 4;
+
 lazy_static! {
     static ref MARGIN: geometry::SideOffsets2D<u16> = geometry::SideOffsets2D::new(1, 2, 1, 2);
     static ref BORDER: geometry::SideOffsets2D<u16> = geometry::SideOffsets2D::new_all_same(1);
     static ref PADDING: geometry::SideOffsets2D<u16> = geometry::SideOffsets2D::new(1, 2, 1, 2);
 }
 
+/// Which way to scroll the help.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum HelpScroll {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+}
+
+/// Lists every key in the keymap and what it does, in as many columns as fit. If the list still
+/// doesn't fit, it can be scrolled.
 #[derive(Debug)]
-pub struct HelpWidget {
+pub struct HelpWidget<'a> {
     pub bounds: geometry::Rect<u16>,
+    pub keymap: &'a Keymap,
+    /// The areas in the game, so that keys for areas the game doesn't have can be left out
+    pub area_ids: &'a [AreaId],
+    /// How many rows the help is scrolled down
+    pub scroll: usize,
+    pub theme: &'a Theme,
 }
 
-impl Widget for HelpWidget {
+impl<'a> Widget for HelpWidget<'a> {
     fn bounds(&self) -> geometry::Rect<u16> {
         self.bounds
     }
 }
 
-impl fmt::Display for HelpWidget {
+impl<'a> fmt::Display for HelpWidget<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         let frame_bounds = self.bounds.inner_rect(*MARGIN);
-        let inner_bounds = frame_bounds.inner_rect(*BORDER + *PADDING);
+        let layout = self.layout();
+
+        let bottom_title = if layout.max_scroll() > 0 {
+            format!(
+                "{} / {} to scroll . . . Any other key to continue",
+                self.scroll_key_name(Action::SelectMore, Key::PageUp),
+                self.scroll_key_name(Action::SelectLess, Key::PageDown),
+            )
+        } else {
+            "Press any key to continue . . .".to_string()
+        };
 
         let frame_display = FrameWidget {
             bounds: frame_bounds,
             top_title: Some(Title::center("H E L P")),
-            bottom_title: Some(Title::right("Press any key to continue . . .")),
-            frame_style: &frame::DOUBLE,
+            bottom_title: Some(Title::right(bottom_title)),
+            frame_style: self.theme.dialog_frame_style(),
+            theme: self.theme,
         };
 
         write!(fmt, "{}", frame_display)?;
 
-        let inner_top_left = inner_bounds.origin;
-        let inner_top_middle = inner_top_left + geometry::vec2(inner_bounds.size.width / 2 + 1, 0);
+        // Clear out whatever was scrolled past.
+        let blank_display = BlankWidget {
+            bounds: layout.bounds,
+            theme: self.theme,
+        };
+        write!(fmt, "{}", blank_display)?;
 
-        for item in left_column_items(inner_top_left) {
-            write!(fmt, "{}", item)?;
-        }
+        let scroll = self.scroll.min(layout.max_scroll());
+        let visible_len = usize::from(layout.bounds.size.height);
+
+        for (column_index, column) in layout.columns.iter().enumerate() {
+            let column_offset =
+                u16::try_from(column_index).unwrap_or(u16::MAX) * layout.column_width;
+
+            for (row_index, item) in column.iter().skip(scroll).take(visible_len).enumerate() {
+                let row_offset = u16::try_from(row_index).unwrap_or(u16::MAX);
+                let item_display = HelpItemWidget {
+                    origin: layout.bounds.origin + geometry::vec2(column_offset, row_offset),
+                    item,
+                    theme: self.theme,
+                };
 
-        for item in right_column_items(inner_top_middle) {
-            write!(fmt, "{}", item)?;
+                write!(fmt, "{}", item_display)?;
+            }
         }
 
         Ok(())
     }
 }
 
-fn left_column_items(origin: geometry::Point2D<u16>) -> Vec<HelpItemWidget> {
-    let mut coord_iter = (0..).map(|index| origin + geometry::vec2(0, index));
+impl<'a> HelpWidget<'a> {
+    /// Where the help would be scrolled to, after scrolling the given way.
+    pub fn scrolled(&self, scroll: HelpScroll) -> usize {
+        let layout = self.layout();
+        let max_scroll = layout.max_scroll();
+        let page_len = usize::from(layout.bounds.size.height).max(1);
+        let current = self.scroll.min(max_scroll);
 
-    vec![
-        HelpItemWidget::Mapping {
-            origin: coord_iter.next().unwrap(),
-            keys: HelpItemKeys::List(vec!["h", "j", "k", "l"]),
-            description: "Move",
-        },
-        HelpItemWidget::Mapping {
-            origin: coord_iter.next().unwrap(),
-            keys: HelpItemKeys::List(vec!["←", "↓", "↑", "→"]),
-            description: "Move",
-        },
-        HelpItemWidget::Skip {
-            origin: coord_iter.next().unwrap(),
-        },
-        HelpItemWidget::Mapping {
-            origin: coord_iter.next().unwrap(),
-            keys: HelpItemKeys::Single("s"),
-            description: "Go to Stock/Deck",
-        },
-        HelpItemWidget::Mapping {
-            origin: coord_iter.next().unwrap(),
-            keys: HelpItemKeys::Single("t"),
-            description: "Go to Talon/Waste",
-        },
-        HelpItemWidget::Mapping {
-            origin: coord_iter.next().unwrap(),
-            keys: HelpItemKeys::Single("f"),
-            description: "Go to next Foundation",
-        },
-        HelpItemWidget::Mapping {
-            origin: coord_iter.next().unwrap(),
-            keys: HelpItemKeys::Single("-"),
-            description: "Go back to previous area",
-        },
-    ]
+        match scroll {
+            HelpScroll::Up => current.saturating_sub(1),
+            HelpScroll::Down => (current + 1).min(max_scroll),
+            HelpScroll::PageUp => current.saturating_sub(page_len),
+            HelpScroll::PageDown => (current + page_len).min(max_scroll),
+        }
+    }
+
+    fn layout(&self) -> HelpLayout {
+        let bounds = self
+            .bounds
+            .inner_rect(*MARGIN)
+            .inner_rect(*BORDER + *PADDING);
+        let items = self.items();
+
+        let item_width = items.iter().map(HelpItem::len).max().unwrap_or_default();
+        let column_width = u16::try_from(item_width)
+            .unwrap_or(u16::MAX)
+            .saturating_add(COLUMN_GAP);
+
+        let column_count = usize::from((bounds.size.width + COLUMN_GAP) / column_width.max(1));
+        let column_count = column_count.max(1);
+        let column_len = ((items.len() + column_count - 1) / column_count).max(1);
+
+        let columns = items
+            .into_iter()
+            .chunks(column_len)
+            .into_iter()
+            .map(|column| {
+                // A gap at the top of a column would just look like a mistake.
+                column
+                    .skip_while(|item| matches!(item, HelpItem::Skip))
+                    .collect()
+            })
+            .collect();
+
+        HelpLayout {
+            bounds,
+            columns,
+            column_width,
+        }
+    }
+
+    /// One item for each binding, with gaps between groups of related commands. Consecutive keys
+    /// for numbered areas, e.g., 1 to 7 for the tableaux, are shown as one item.
+    fn items(&self) -> Vec<HelpItem> {
+        let bindings = self
+            .keymap
+            .bindings()
+            .iter()
+            .filter(|binding| !binding.keys.is_empty())
+            .filter(|binding| match binding.command {
+                Command::Action(Action::MoveTo(area_id)) => self.area_ids.contains(&area_id),
+                _ => true,
+            })
+            .collect::<Vec<_>>();
+
+        let mut items = vec![];
+        let mut prev_group = None;
+
+        for run in numbered_runs(&bindings) {
+            let first = run[0];
+            let group = command_group(first.command);
+
+            if prev_group.map_or(false, |prev_group| prev_group != group) {
+                items.push(HelpItem::Skip);
+            }
+            prev_group = Some(group);
+
+            if let [_, .., last] = run.as_slice() {
+                items.push(HelpItem::Mapping {
+                    keys: HelpItemKeys::Range(
+                        key_label(first.keys[0], self.theme),
+                        key_label(last.keys[0], self.theme),
+                    ),
+                    description: area_description(first.command).to_string(),
+                });
+            } else {
+                items.push(HelpItem::Mapping {
+                    keys: HelpItemKeys::List(
+                        first
+                            .keys
+                            .iter()
+                            .map(|&key| key_label(key, self.theme))
+                            .collect(),
+                    ),
+                    description: describe(first.command),
+                });
+            }
+        }
+
+        items
+    }
+
+    fn scroll_key_name(&self, action: Action, fallback: Key) -> String {
+        let key = self
+            .keymap
+            .keys_for(Command::Action(action))
+            .first()
+            .copied()
+            .unwrap_or(fallback);
+        key_label(key, self.theme)
+    }
 }
 
-fn right_column_items(origin: geometry::Point2D<u16>) -> Vec<HelpItemWidget> {
-    let mut coord_iter = (0..).map(|index| origin + geometry::vec2(0, index));
+#[derive(Debug)]
+struct HelpLayout {
+    /// Where the items go, inside the frame
+    bounds: geometry::Rect<u16>,
+    columns: Vec<Vec<HelpItem>>,
+    column_width: u16,
+}
 
-    vec![
-        HelpItemWidget::Mapping {
-            origin: coord_iter.next().unwrap(),
-            keys: HelpItemKeys::Range("F1", "F4"),
-            description: "Go to Foundation",
-        },
-        HelpItemWidget::Mapping {
-            origin: coord_iter.next().unwrap(),
-            keys: HelpItemKeys::Range("1", "7"),
-            description: "Go to Tableaux",
-        },
-        HelpItemWidget::Skip {
-            origin: coord_iter.next().unwrap(),
-        },
-        HelpItemWidget::Mapping {
-            origin: coord_iter.next().unwrap(),
-            keys: HelpItemKeys::List(vec!["SPACE", "RETURN"]),
-            description: "Pick Up/Activate",
-        },
-        HelpItemWidget::Mapping {
-            origin: coord_iter.next().unwrap(),
-            keys: HelpItemKeys::Single("ESC"),
-            description: "Return Held Cards",
-        },
-        HelpItemWidget::Skip {
-            origin: coord_iter.next().unwrap(),
-        },
-        HelpItemWidget::Mapping {
-            origin: coord_iter.next().unwrap(),
-            keys: HelpItemKeys::Single("?"),
-            description: "Help",
-        },
-        HelpItemWidget::Mapping {
-            origin: coord_iter.next().unwrap(),
-            keys: HelpItemKeys::Single("q"),
-            description: "Quit",
+impl HelpLayout {
+    fn max_scroll(&self) -> usize {
+        let row_len = self.columns.iter().map(Vec::len).max().unwrap_or_default();
+        row_len.saturating_sub(usize::from(self.bounds.size.height))
+    }
+}
+
+/// Split the bindings into runs that can be shown as one item. Most runs are a single binding.
+fn numbered_runs<'b>(bindings: &[&'b Binding]) -> Vec<Vec<&'b Binding>> {
+    let mut runs: Vec<Vec<&Binding>> = vec![];
+
+    for &binding in bindings {
+        match runs.last_mut() {
+            Some(run) if is_numbered_run(run[run.len() - 1], binding) => run.push(binding),
+            _ => runs.push(vec![binding]),
+        }
+    }
+
+    runs
+}
+
+/// Whether two bindings go to consecutive areas of the same kind with one key each, and the keys
+/// are consecutive too, so they can be shown together.
+fn is_numbered_run(binding: &Binding, next: &Binding) -> bool {
+    let consecutive = match (binding.command, next.command) {
+        (
+            Command::Action(Action::MoveTo(AreaId::Tableaux(index))),
+            Command::Action(Action::MoveTo(AreaId::Tableaux(next_index))),
+        )
+        | (
+            Command::Action(Action::MoveTo(AreaId::Foundation(index))),
+            Command::Action(Action::MoveTo(AreaId::Foundation(next_index))),
+        ) => index.checked_add(1) == Some(next_index),
+        _ => false,
+    };
+
+    let consecutive_keys = match (binding.keys.as_slice(), next.keys.as_slice()) {
+        ([Key::Char(key)], [Key::Char(next_key)]) => u32::from(*key) + 1 == u32::from(*next_key),
+        ([Key::F(key)], [Key::F(next_key)]) => key.checked_add(1) == Some(*next_key),
+        _ => false,
+    };
+
+    consecutive && consecutive_keys
+}
+
+/// Commands that go together in the help, so that there's a gap between each group.
+fn command_group(command: Command) -> u8 {
+    match command {
+        Command::Action(Action::MoveLeft)
+        | Command::Action(Action::MoveRight)
+        | Command::Action(Action::SelectMore)
+        | Command::Action(Action::SelectLess) => 0,
+        Command::Action(Action::MoveTo(AreaId::Tableaux(_)))
+        | Command::Action(Action::MoveTo(AreaId::Foundation(_))) => 2,
+        Command::Action(Action::MoveTo(_))
+        | Command::Action(Action::MoveToFoundation)
+        | Command::Action(Action::MoveBack) => 1,
+        Command::Action(Action::Activate) | Command::Action(Action::ReturnHeld) => 3,
+        Command::Action(Action::Undo)
+        | Command::Action(Action::Redo)
+        | Command::Action(Action::Hint) => 4,
+        _ => 5,
+    }
+}
+
+fn area_description(command: Command) -> &'static str {
+    match command {
+        Command::Action(Action::MoveTo(AreaId::Foundation(_))) => "Go to Foundation",
+        _ => "Go to Tableaux",
+    }
+}
+
+fn describe(command: Command) -> String {
+    let description = match command {
+        Command::Action(action) => match action {
+            Action::MoveTo(AreaId::Stock) => "Go to Stock/Deck",
+            Action::MoveTo(AreaId::Talon) => "Go to Talon/Waste",
+            Action::MoveTo(area_id @ AreaId::Tableaux(index))
+            | Action::MoveTo(area_id @ AreaId::Foundation(index)) => {
+                return format!(
+                    "{} {}",
+                    area_description(Command::Action(Action::MoveTo(area_id))),
+                    u16::from(index) + 1
+                );
+            }
+            Action::MoveBack => "Go back to previous area",
+            Action::MoveToFoundation => "Go to next Foundation",
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::SelectMore => "Select more cards",
+            Action::SelectLess => "Select fewer cards",
+            Action::Activate => "Pick Up/Activate",
+            Action::ReturnHeld => "Return Held Cards",
+            Action::Undo => "Undo",
+            Action::Redo => "Redo",
+            Action::Hint => "Hint",
         },
-    ]
+        Command::NewGame => "New Game",
+        Command::ReplayGame => "Replay Deal",
+        Command::AutoComplete => "Auto-Complete",
+        Command::Help => "Help",
+        Command::Statistics => "Statistics",
+        Command::Quit => "Quit",
+    };
+
+    description.to_string()
+}
+
+/// How a key is shown in the help, and anywhere else the player is told which key to press.
+pub fn key_label(key: Key, theme: &Theme) -> String {
+    match key {
+        Key::Char(' ') => "SPACE".to_string(),
+        Key::Char('\n') => "RETURN".to_string(),
+        Key::Char('\t') => "TAB".to_string(),
+        Key::Char(c) => c.to_string(),
+        Key::Ctrl(c) => format!("CTRL-{}", c.to_uppercase()),
+        Key::Alt(c) => format!("ALT-{}", c),
+        Key::F(number) => format!("F{}", number),
+        Key::Left => theme.unicode_or("←", "LEFT").to_string(),
+        Key::Right => theme.unicode_or("→", "RIGHT").to_string(),
+        Key::Up => theme.unicode_or("↑", "UP").to_string(),
+        Key::Down => theme.unicode_or("↓", "DOWN").to_string(),
+        Key::Esc => "ESC".to_string(),
+        Key::Backspace => "BACKSPACE".to_string(),
+        Key::Delete => "DEL".to_string(),
+        Key::Insert => "INS".to_string(),
+        Key::Home => "HOME".to_string(),
+        Key::End => "END".to_string(),
+        Key::PageUp => "PGUP".to_string(),
+        Key::PageDown => "PGDN".to_string(),
+        other => format!("{:?}", other),
+    }
 }
 
 #[derive(Debug)]
 enum HelpItemKeys {
-    Single(&'static str),
-    List(Vec<&'static str>),
-    Range(&'static str, &'static str),
+    List(Vec<String>),
+    Range(String, String),
 }
 
 impl HelpItemKeys {
     fn len(&self) -> usize {
         match self {
-            Self::Single(key) => key.char_len(),
             Self::List(keys) => keys.iter().map(|key| key.char_len()).intersperse(3).sum(),
             Self::Range(start_key, end_key) => start_key.char_len() + end_key.char_len() + 5,
         }
     }
-}
 
-impl fmt::Display for HelpItemKeys {
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fn write(&self, theme: &Theme, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Single(key) => {
-                write!(fmt, "{key_style}{key}", key_style = key_style(), key = key)?;
-            }
             Self::List(keys) => {
                 let formatted_keys_iter = keys
                     .iter()
-                    .map(|key| FormattedString::new_with_formatting(key_style()).push_content(key))
+                    .map(|key| {
+                        FormattedString::new_with_formatting(key_style(theme)).push_content(key)
+                    })
                     .intersperse(
-                        FormattedString::new_with_formatting(reset_style()).push_content(" / "),
+                        FormattedString::new_with_formatting(reset_style(theme))
+                            .push_content(" / "),
                     );
 
                 for formatted_key in formatted_keys_iter {
@@ -177,8 +401,8 @@ impl fmt::Display for HelpItemKeys {
                 write!(
                     fmt,
                     "{key_style}{start_key}{reset} ... {key_style}{end_key}",
-                    reset = reset_style(),
-                    key_style = key_style(),
+                    reset = reset_style(theme),
+                    key_style = key_style(theme),
                     start_key = start_key,
                     end_key = end_key,
                 )?;
@@ -190,50 +414,48 @@ impl fmt::Display for HelpItemKeys {
 }
 
 #[derive(Debug)]
-enum HelpItemWidget {
+enum HelpItem {
     Mapping {
-        origin: geometry::Point2D<u16>,
         keys: HelpItemKeys,
-        description: &'static str,
-    },
-    Skip {
-        origin: geometry::Point2D<u16>,
+        description: String,
     },
+    Skip,
 }
 
-impl Widget for HelpItemWidget {
-    fn bounds(&self) -> geometry::Rect<u16> {
+impl HelpItem {
+    fn len(&self) -> usize {
         match self {
-            Self::Mapping {
-                origin,
-                keys,
-                description,
-            } => {
-                let length = u16::try_from(keys.len() + description.char_len() + 4).unwrap();
-                geometry::Rect::new(*origin, geometry::size2(length, 0))
-            }
-            Self::Skip { origin } => geometry::Rect::new(*origin, geometry::Size2D::zero()),
+            Self::Mapping { keys, description } => keys.len() + description.char_len() + 4,
+            Self::Skip => 0,
         }
     }
 }
 
-impl fmt::Display for HelpItemWidget {
+#[derive(Debug)]
+struct HelpItemWidget<'a> {
+    origin: geometry::Point2D<u16>,
+    item: &'a HelpItem,
+    theme: &'a Theme,
+}
+
+impl<'a> Widget for HelpItemWidget<'a> {
+    fn bounds(&self) -> geometry::Rect<u16> {
+        let length = u16::try_from(self.item.len()).unwrap_or(u16::MAX);
+        geometry::Rect::new(self.origin, geometry::size2(length, 0))
+    }
+}
+
+impl<'a> fmt::Display for HelpItemWidget<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Self::Mapping {
-            origin,
-            keys,
-            description,
-        } = self
-        {
-            let goto = geometry::goto(*origin);
+        if let HelpItem::Mapping { keys, description } = self.item {
+            write!(fmt, "{}", geometry::goto(self.origin))?;
+            keys.write(self.theme, fmt)?;
 
             write!(
                 fmt,
-                "{goto}{keys}{reset} :  {desc_style}{desc}",
-                goto = goto,
-                reset = reset_style(),
-                desc_style = description_style(),
-                keys = keys,
+                "{reset} :  {desc_style}{desc}",
+                reset = reset_style(self.theme),
+                desc_style = description_style(self.theme),
                 desc = description,
             )?;
         }
@@ -242,14 +464,14 @@ impl fmt::Display for HelpItemWidget {
     }
 }
 
-fn key_style() -> impl fmt::Display {
-    color::Fg(color::Cyan)
+fn key_style(theme: &Theme) -> impl fmt::Display {
+    theme.fg(color::Cyan)
 }
 
-fn reset_style() -> impl fmt::Display {
-    color::Fg(color::Reset)
+fn reset_style(theme: &Theme) -> impl fmt::Display {
+    theme.fg(color::Reset)
 }
 
-fn description_style() -> impl fmt::Display {
-    color::Fg(color::White)
+fn description_style(theme: &Theme) -> impl fmt::Display {
+    theme.fg(color::White)
 }
@@ -11,12 +11,16 @@ pub mod geometry;
 pub mod help;
 pub mod selector;
 pub mod stack;
+pub mod stats;
+pub mod status;
+pub mod theme;
 pub mod win;
 
 #[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
 pub enum DisplayState {
     Playing,
     HelpMessageOpen,
+    StatisticsOpen,
     Quitting,
     WinMessageOpen,
 }
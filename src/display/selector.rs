@@ -1,24 +1,44 @@
 use std::fmt;
-use termion::{color, cursor};
+use termion::cursor;
 
 use crate::model::stack::Orientation;
 
-use super::{geometry, Widget};
+use super::{geometry, theme::Theme, Widget};
 
-mod horizontal {
-    use super::{fmt, geometry};
-
-    pub fn write_start(fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(fmt, "╘")
-    }
-
-    pub fn write_middle(fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(fmt, "═")
-    }
+/// The pieces a selector is drawn with: one for each end, and one for everything in between.
+#[derive(Debug)]
+pub struct SelectorGlyphs {
+    pub start: &'static str,
+    pub middle: &'static str,
+    pub end: &'static str,
+}
 
-    pub fn write_end(fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(fmt, "╛")
-    }
+mod horizontal {
+    use super::{fmt, geometry, SelectorGlyphs};
+
+    pub static HEAVY: SelectorGlyphs = SelectorGlyphs {
+        start: "╘",
+        middle: "═",
+        end: "╛",
+    };
+
+    pub static LIGHT: SelectorGlyphs = SelectorGlyphs {
+        start: "└",
+        middle: "─",
+        end: "┘",
+    };
+
+    pub static ASCII_HEAVY: SelectorGlyphs = SelectorGlyphs {
+        start: "#",
+        middle: "=",
+        end: "#",
+    };
+
+    pub static ASCII_LIGHT: SelectorGlyphs = SelectorGlyphs {
+        start: "'",
+        middle: "-",
+        end: "'",
+    };
 
     pub fn write_next(_fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         Ok(())
@@ -30,19 +50,31 @@ mod horizontal {
 }
 
 mod vertical {
-    use super::{cursor, fmt, geometry};
-
-    pub fn write_start(fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(fmt, "╓╴")
-    }
-
-    pub fn write_middle(fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(fmt, "║ ")
-    }
-
-    pub fn write_end(fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(fmt, "╙╴")
-    }
+    use super::{cursor, fmt, geometry, SelectorGlyphs};
+
+    pub static HEAVY: SelectorGlyphs = SelectorGlyphs {
+        start: "╓╴",
+        middle: "║ ",
+        end: "╙╴",
+    };
+
+    pub static LIGHT: SelectorGlyphs = SelectorGlyphs {
+        start: "┌╴",
+        middle: "│ ",
+        end: "└╴",
+    };
+
+    pub static ASCII_HEAVY: SelectorGlyphs = SelectorGlyphs {
+        start: "#=",
+        middle: "# ",
+        end: "#=",
+    };
+
+    pub static ASCII_LIGHT: SelectorGlyphs = SelectorGlyphs {
+        start: ".-",
+        middle: "| ",
+        end: "'-",
+    };
 
     pub fn write_next(fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(fmt, "{}{}", cursor::Left(2), cursor::Down(1))
@@ -53,14 +85,25 @@ mod vertical {
     }
 }
 
+/// What a selector is pointing out, which decides how it's drawn.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SelectorStyle {
+    /// The cards the player has selected
+    Selection,
+    /// The cards of a suggested move
+    Hint,
+}
+
 #[derive(Debug)]
-pub struct SelectorWidget {
+pub struct SelectorWidget<'a> {
     pub origin: geometry::Point2D<u16>,
     pub len: u16,
     pub orientation: Orientation,
+    pub style: SelectorStyle,
+    pub theme: &'a Theme,
 }
 
-impl Widget for SelectorWidget {
+impl<'a> Widget for SelectorWidget<'a> {
     fn bounds(&self) -> geometry::Rect<u16> {
         match self.orientation {
             Orientation::Horizontal => horizontal::bounds(self.origin, self.len),
@@ -69,20 +112,31 @@ impl Widget for SelectorWidget {
     }
 }
 
-impl fmt::Display for SelectorWidget {
+impl<'a> fmt::Display for SelectorWidget<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         let start = geometry::goto(self.origin);
-        let color = color::Fg(color::LightWhite);
 
-        write!(fmt, "{}{}", start, color)?;
+        match self.style {
+            SelectorStyle::Selection => write!(
+                fmt,
+                "{}{}",
+                start,
+                self.theme.fg(self.theme.style.selection)
+            )?,
+            SelectorStyle::Hint => {
+                write!(fmt, "{}{}", start, self.theme.fg(self.theme.style.hint))?
+            }
+        }
+
+        let glyphs = self.glyphs();
 
         for i in 0..self.len {
             if i == 0 {
-                self.write_start(fmt)?;
+                write!(fmt, "{}", glyphs.start)?;
             } else if i == self.len - 1 {
-                self.write_end(fmt)?;
+                write!(fmt, "{}", glyphs.end)?;
             } else {
-                self.write_middle(fmt)?;
+                write!(fmt, "{}", glyphs.middle)?;
             }
 
             self.write_next(fmt)?;
@@ -92,25 +146,24 @@ impl fmt::Display for SelectorWidget {
     }
 }
 
-impl SelectorWidget {
-    fn write_start(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.orientation {
-            Orientation::Horizontal => horizontal::write_start(fmt),
-            Orientation::Vertical => vertical::write_start(fmt),
-        }
-    }
-
-    fn write_middle(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.orientation {
-            Orientation::Horizontal => horizontal::write_middle(fmt),
-            Orientation::Vertical => vertical::write_middle(fmt),
-        }
-    }
-
-    fn write_end(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.orientation {
-            Orientation::Horizontal => horizontal::write_end(fmt),
-            Orientation::Vertical => vertical::write_end(fmt),
+impl<'a> SelectorWidget<'a> {
+    fn glyphs(&self) -> &'static SelectorGlyphs {
+        // Without colour, hints are drawn in light lines to tell them apart from the selection.
+        let light = self.style == SelectorStyle::Hint && !self.theme.color;
+
+        match (self.orientation, light) {
+            (Orientation::Horizontal, false) => self
+                .theme
+                .unicode_or(&horizontal::HEAVY, &horizontal::ASCII_HEAVY),
+            (Orientation::Horizontal, true) => self
+                .theme
+                .unicode_or(&horizontal::LIGHT, &horizontal::ASCII_LIGHT),
+            (Orientation::Vertical, false) => self
+                .theme
+                .unicode_or(&vertical::HEAVY, &vertical::ASCII_HEAVY),
+            (Orientation::Vertical, true) => self
+                .theme
+                .unicode_or(&vertical::LIGHT, &vertical::ASCII_LIGHT),
         }
     }
 
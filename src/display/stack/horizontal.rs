@@ -1,11 +1,11 @@
-use std::convert::TryFrom;
+use std::{cmp::min, convert::TryFrom};
 
 use log::debug;
 
 use crate::display::{
     card::{CardWidget, CardWidgetMode, CARD_SIZE},
     geometry,
-    selector::SelectorWidget,
+    selector::{SelectorStyle, SelectorWidget},
 };
 
 use super::{
@@ -14,9 +14,9 @@ use super::{
 };
 
 lazy_static! {
-    static ref OFFSETS: Offsets = Offsets {
+    static ref UNCOLLAPSED_OFFSETS: Offsets = Offsets {
         unspread: geometry::vec2(1, 0),
-        collapsed_spread: geometry::vec2(4, 0),
+        collapsed_spread: geometry::vec2(1, 0),
         uncollapsed_spread: geometry::vec2(4, 0),
         selected: geometry::vec2(1, 0),
         collapse_unspread_len: 0,
@@ -24,8 +24,48 @@ lazy_static! {
     };
 }
 
-pub fn offsets(_widget: &StackWidget<'_>) -> Offsets {
-    OFFSETS.clone()
+/// A spread too wide for our bounds, e.g., an open stock, is squeezed by collapsing its bottom
+/// cards, starting from the left.
+pub fn offsets(widget: &StackWidget<'_>) -> Offsets {
+    let details = &widget.stack.details;
+
+    let mut offsets = UNCOLLAPSED_OFFSETS.clone();
+    let collapse_len = collapse_len(widget, &offsets);
+
+    debug!("collapse_len: {}", collapse_len);
+
+    if collapse_len > 0 {
+        let saved_per_card =
+            u16::try_from(offsets.uncollapsed_spread.x - offsets.collapsed_spread.x).unwrap();
+        let collapse_spread_len = usize::from((collapse_len + saved_per_card - 1) / saved_per_card);
+
+        offsets.collapse_spread_len =
+            min(details.spread_len.saturating_sub(1), collapse_spread_len);
+    }
+
+    offsets
+}
+
+fn collapse_len(widget: &StackWidget<'_>, offsets: &Offsets) -> u16 {
+    if widget.stack.cards.is_empty() {
+        return 0;
+    }
+
+    let origin = widget.bounds.origin;
+    let maximum_x = widget.bounds.max_x();
+
+    let last_card_coords = card_coords(
+        origin,
+        widget.stack.cards.len() - 1,
+        offsets,
+        &widget.stack.details,
+    )
+    .unwrap_or_default();
+
+    let uncollapsed_bounds = geometry::Rect::new(last_card_coords, *CARD_SIZE);
+    let uncollapsed_x = uncollapsed_bounds.max_x();
+
+    uncollapsed_x.saturating_sub(maximum_x)
 }
 
 pub fn card_widget_iter<'a>(
@@ -47,41 +87,65 @@ pub fn card_widget_iter<'a>(
             card,
             origin: coords,
             mode,
+            theme: widget.theme,
         }
     })
 }
 
-pub fn selector_widget(widget: &StackWidget<'_>, offsets: &Offsets) -> Option<SelectorWidget> {
+pub fn selector_widget<'a>(
+    widget: &StackWidget<'a>,
+    offsets: &Offsets,
+) -> Option<SelectorWidget<'a>> {
+    widget
+        .stack
+        .details
+        .selection
+        .as_ref()
+        .map(|selection| marker_widget(widget, offsets, selection.len, SelectorStyle::Selection))
+}
+
+pub fn hint_widget<'a>(widget: &StackWidget<'a>, offsets: &Offsets) -> Option<SelectorWidget<'a>> {
+    widget
+        .hint_len
+        .map(|len| marker_widget(widget, offsets, len, SelectorStyle::Hint))
+}
+
+fn marker_widget<'a>(
+    widget: &StackWidget<'a>,
+    offsets: &Offsets,
+    len: usize,
+    style: SelectorStyle,
+) -> SelectorWidget<'a> {
     let coords = widget.bounds.origin;
     let details = &widget.stack.details;
 
-    details.selection.as_ref().map(|_| {
-        let selection_index = details.selection_index().unwrap_or_default();
+    let start_index = details.len.saturating_sub(len);
 
-        debug!("selection_index: {}", selection_index);
+    debug!("start_index: {}", start_index);
 
-        /* Be careful about getting the last index. It's possible for the stack to actually be empty,
-         * in which case we can't subtract from a 0 usize. */
-        let end_index = details.len.saturating_sub(1);
+    /* Be careful about getting the last index. It's possible for the stack to actually be empty,
+     * in which case we can't subtract from a 0 usize. */
+    let end_index = details.len.saturating_sub(1);
 
-        let start_coords = card_coords(coords, selection_index, offsets, details)
-            .unwrap_or(coords)
-            .cast::<i16>()
-            + geometry::vec2(0, CARD_SIZE.height).cast::<i16>();
-        let end_coords = card_coords(coords, end_index, offsets, details)
-            .unwrap_or(coords)
-            .cast::<i16>()
-            + CARD_SIZE.cast::<i16>();
+    let start_coords = card_coords(coords, start_index, offsets, details)
+        .unwrap_or(coords)
+        .cast::<i16>()
+        + geometry::vec2(0, CARD_SIZE.height).cast::<i16>();
+    let end_coords = card_coords(coords, end_index, offsets, details)
+        .unwrap_or(coords)
+        .cast::<i16>()
+        + CARD_SIZE.cast::<i16>();
 
-        debug!("start_coords: {:?}", start_coords);
-        debug!("end_coords: {:?}", end_coords);
+    debug!("start_coords: {:?}", start_coords);
+    debug!("end_coords: {:?}", end_coords);
 
-        let len = u16::try_from(end_coords.x - start_coords.x).unwrap();
+    let len = u16::try_from(end_coords.x - start_coords.x).unwrap();
 
-        SelectorWidget {
-            origin: start_coords.cast::<u16>(),
-            len,
-            orientation: details.orientation,
-        }
-    })
+    SelectorWidget {
+        origin: start_coords.cast::<u16>(),
+        len,
+        orientation: details.orientation,
+        style,
+        theme: widget.theme,
+    }
 }
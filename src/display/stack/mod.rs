@@ -2,7 +2,13 @@ use std::fmt;
 
 use crate::model::stack::{Orientation, Stack};
 
-use super::{card::CardWidget, geometry, selector::SelectorWidget, Widget};
+use super::{
+    card::{CardWidget, EmptySlotWidget},
+    geometry,
+    selector::SelectorWidget,
+    theme::Theme,
+    Widget,
+};
 
 use self::common::Offsets;
 
@@ -14,6 +20,12 @@ mod vertical;
 pub struct StackWidget<'a> {
     pub bounds: geometry::Rect<u16>,
     pub stack: &'a Stack<'a>,
+    /// The number of cards at the top of the stack to point out as part of a hint, if any
+    pub hint_len: Option<usize>,
+    /// Whether the stack is empty for good, e.g., a stock with no passes remaining. An exhausted
+    /// stack with no cards is marked with an empty slot.
+    pub exhausted: bool,
+    pub theme: &'a Theme,
 }
 
 impl<'a> Widget for StackWidget<'a> {
@@ -26,10 +38,18 @@ impl<'a> Widget for StackWidget<'a> {
             bounds = bounds.union(&card_widget.bounds());
         }
 
+        if let Some(empty_slot_widget) = self.empty_slot_widget() {
+            bounds = bounds.union(&empty_slot_widget.bounds());
+        }
+
         if let Some(selector_widget) = self.selector_widget(&offsets) {
             bounds = bounds.union(&selector_widget.bounds());
         }
 
+        if let Some(hint_widget) = self.hint_widget(&offsets) {
+            bounds = bounds.union(&hint_widget.bounds());
+        }
+
         bounds
     }
 }
@@ -42,10 +62,19 @@ impl<'a> fmt::Display for StackWidget<'a> {
             write!(fmt, "{}", card_widget)?;
         }
 
+        if let Some(empty_slot_widget) = self.empty_slot_widget() {
+            write!(fmt, "{}", empty_slot_widget)?;
+        }
+
         if let Some(selector_widget) = self.selector_widget(&offsets) {
             write!(fmt, "{}", selector_widget)?;
         }
 
+        // Draw the hint over the selector, since it's the more recent thing the player asked for.
+        if let Some(hint_widget) = self.hint_widget(&offsets) {
+            write!(fmt, "{}", hint_widget)?;
+        }
+
         Ok(())
     }
 }
@@ -81,10 +110,28 @@ impl<'a> StackWidget<'a> {
         horizontal_iter.chain(vertical_iter)
     }
 
-    fn selector_widget(&self, offsets: &Offsets) -> Option<SelectorWidget> {
+    fn empty_slot_widget(&self) -> Option<EmptySlotWidget<'_>> {
+        if self.exhausted && self.stack.cards.is_empty() {
+            Some(EmptySlotWidget {
+                origin: self.bounds.origin,
+                theme: self.theme,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn selector_widget(&self, offsets: &Offsets) -> Option<SelectorWidget<'_>> {
         match self.stack.details.orientation {
             Orientation::Horizontal => horizontal::selector_widget(self, offsets),
             Orientation::Vertical => vertical::selector_widget(self, offsets),
         }
     }
+
+    fn hint_widget(&self, offsets: &Offsets) -> Option<SelectorWidget<'_>> {
+        match self.stack.details.orientation {
+            Orientation::Horizontal => horizontal::hint_widget(self, offsets),
+            Orientation::Vertical => vertical::hint_widget(self, offsets),
+        }
+    }
 }
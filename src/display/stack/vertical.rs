@@ -5,7 +5,7 @@ use log::debug;
 use crate::display::{
     card::{CardWidget, CardWidgetMode, CARD_SIZE},
     geometry,
-    selector::SelectorWidget,
+    selector::{SelectorStyle, SelectorWidget},
 };
 
 use super::{
@@ -108,45 +108,73 @@ pub fn card_widget_iter<'a>(
             card,
             origin: coords,
             mode,
+            theme: widget.theme,
         }
     })
 }
 
-pub fn selector_widget(widget: &StackWidget<'_>, offsets: &Offsets) -> Option<SelectorWidget> {
+pub fn selector_widget<'a>(
+    widget: &StackWidget<'a>,
+    offsets: &Offsets,
+) -> Option<SelectorWidget<'a>> {
+    widget.stack.details.selection.as_ref().map(|selection| {
+        marker_widget(
+            widget,
+            offsets,
+            selection.len,
+            selection.held,
+            SelectorStyle::Selection,
+        )
+    })
+}
+
+pub fn hint_widget<'a>(widget: &StackWidget<'a>, offsets: &Offsets) -> Option<SelectorWidget<'a>> {
+    widget
+        .hint_len
+        .map(|len| marker_widget(widget, offsets, len, false, SelectorStyle::Hint))
+}
+
+fn marker_widget<'a>(
+    widget: &StackWidget<'a>,
+    offsets: &Offsets,
+    len: usize,
+    held: bool,
+    style: SelectorStyle,
+) -> SelectorWidget<'a> {
     let coords = widget.bounds.origin;
     let details = &widget.stack.details;
 
-    details.selection.as_ref().map(|selection| {
-        let selection_index = details.selection_index().unwrap_or_default();
+    let start_index = details.len.saturating_sub(len);
 
-        // Be careful about getting the last index. It's possible for the stack to actually be
-        // empty, in which case we can't subtract from a 0 usize.
-        let end_index = details.len.saturating_sub(1);
+    // Be careful about getting the last index. It's possible for the stack to actually be
+    // empty, in which case we can't subtract from a 0 usize.
+    let end_index = details.len.saturating_sub(1);
 
-        let held_offset = if selection.held {
-            -UNCOLLAPSED_OFFSETS.selected
-        } else {
-            Default::default()
-        };
+    let held_offset = if held {
+        -UNCOLLAPSED_OFFSETS.selected
+    } else {
+        Default::default()
+    };
 
-        let start_coords = card_coords(coords, selection_index, offsets, details)
-            .unwrap_or(coords)
-            .cast::<i16>()
-            + *SELECTOR_OFFSET
-            + held_offset;
-        let end_coords = card_coords(coords, end_index, offsets, details)
-            .unwrap_or(coords)
-            .cast::<i16>()
-            + geometry::vec2(0, CARD_SIZE.height).cast::<i16>()
-            + *SELECTOR_OFFSET
-            + held_offset;
-
-        let len = u16::try_from(end_coords.y - start_coords.y).unwrap();
-
-        SelectorWidget {
-            origin: start_coords.cast::<u16>(),
-            len,
-            orientation: details.orientation,
-        }
-    })
+    let start_coords = card_coords(coords, start_index, offsets, details)
+        .unwrap_or(coords)
+        .cast::<i16>()
+        + *SELECTOR_OFFSET
+        + held_offset;
+    let end_coords = card_coords(coords, end_index, offsets, details)
+        .unwrap_or(coords)
+        .cast::<i16>()
+        + geometry::vec2(0, CARD_SIZE.height).cast::<i16>()
+        + *SELECTOR_OFFSET
+        + held_offset;
+
+    let len = u16::try_from(end_coords.y - start_coords.y).unwrap();
+
+    SelectorWidget {
+        origin: start_coords.cast::<u16>(),
+        len,
+        orientation: details.orientation,
+        style,
+        theme: widget.theme,
+    }
 }
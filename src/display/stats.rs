@@ -0,0 +1,161 @@
+//! Module for the statistics screen, summing up every game played so far.
+
+use std::{convert::TryFrom, fmt};
+use termion::color;
+
+use crate::{model::stats::Statistics, utils::str::CharacterLength};
+
+use super::{
+    frame::{FrameWidget, Title},
+    geometry,
+    status::format_duration,
+    theme::Theme,
+    Widget,
+};
+
+/// How many cards each bar of the histogram covers
+static HISTOGRAM_BUCKET_LEN: usize = 10;
+
+/// The longest a bar of the histogram can be, for the bucket with the most games
+static HISTOGRAM_BAR_LEN: usize = 20;
+
+static CONTENT_WIDTH: u16 = 36;
+
+lazy_static! {
+    static ref BORDER: geometry::SideOffsets2D<u16> = geometry::SideOffsets2D::new_all_same(1);
+    static ref PADDING: geometry::SideOffsets2D<u16> = geometry::SideOffsets2D::new(1, 2, 1, 2);
+}
+
+#[derive(Debug)]
+pub struct StatsWidget<'a> {
+    pub bounds: geometry::Rect<u16>,
+    pub statistics: &'a Statistics,
+    pub theme: &'a Theme,
+}
+
+impl<'a> Widget for StatsWidget<'a> {
+    fn bounds(&self) -> geometry::Rect<u16> {
+        let content_height = u16::try_from(self.lines().len()).unwrap_or(u16::MAX);
+        let content_size = geometry::size2(CONTENT_WIDTH, content_height);
+
+        let left_offset = self.bounds.size.width.saturating_sub(content_size.width) / 2;
+        let top_offset = self.bounds.size.height.saturating_sub(content_size.height) / 2;
+        let offset: geometry::Vector2D<u16> = geometry::vec2(left_offset, top_offset);
+
+        let inner_origin = self.bounds.origin + offset;
+        let inner_bounds = geometry::Rect::new(inner_origin, content_size);
+
+        inner_bounds.outer_rect(*BORDER + *PADDING)
+    }
+}
+
+impl<'a> fmt::Display for StatsWidget<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frame_bounds = self.bounds();
+        let inner_bounds = frame_bounds.inner_rect(*BORDER + *PADDING);
+
+        let frame_display = FrameWidget {
+            bounds: frame_bounds,
+            top_title: Some(Title::center("S T A T I S T I C S")),
+            bottom_title: Some(Title::right("Press any key to continue . . .")),
+            frame_style: self.theme.dialog_frame_style(),
+            theme: self.theme,
+        };
+
+        write!(fmt, "{}", frame_display)?;
+
+        for (index, line) in self.lines().into_iter().enumerate() {
+            let index = u16::try_from(index).unwrap_or(u16::MAX);
+            let goto = geometry::goto(inner_bounds.origin + geometry::vec2(0, index));
+            write!(fmt, "{}{}", goto, line)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> StatsWidget<'a> {
+    fn lines(&self) -> Vec<String> {
+        let statistics = self.statistics;
+
+        if statistics.records.is_empty() {
+            return vec![format!("{}No games played yet", label_style(self.theme))];
+        }
+
+        let win_rate = statistics.win_rate().unwrap_or_default();
+        let fastest_win = statistics
+            .fastest_win()
+            .map(format_duration)
+            .unwrap_or_else(|| self.theme.unicode_or("—", "-").to_string());
+
+        let mut lines = vec![
+            stat_line(self.theme, "Games played", statistics.games_len()),
+            stat_line(
+                self.theme,
+                "Games won",
+                format!(
+                    "{} ({:.0}%)",
+                    statistics.wins_len(),
+                    (win_rate * 100.0).round()
+                ),
+            ),
+            stat_line(self.theme, "Current streak", statistics.current_streak()),
+            stat_line(self.theme, "Best streak", statistics.best_streak()),
+            stat_line(self.theme, "Fastest win", fastest_win),
+            String::new(),
+            format!("{}Cards on foundations", label_style(self.theme)),
+        ];
+
+        let histogram = statistics.foundation_histogram(HISTOGRAM_BUCKET_LEN);
+        let max_count = histogram
+            .iter()
+            .map(|&(_, count)| count)
+            .max()
+            .unwrap_or_default()
+            .max(1);
+
+        for (start, count) in histogram {
+            let range = format!(
+                "{}{}{}",
+                start,
+                self.theme.unicode_or("–", "-"),
+                start + HISTOGRAM_BUCKET_LEN - 1
+            );
+            let bar_len = (count * HISTOGRAM_BAR_LEN + max_count - 1) / max_count;
+
+            lines.push(format!(
+                "{label_style}{range}{padding}{bar_style}{bar} {value_style}{count}",
+                label_style = label_style(self.theme),
+                range = range,
+                padding = " ".repeat(8_usize.saturating_sub(range.char_len())),
+                bar_style = self.theme.fg(color::LightBlue),
+                bar = self.theme.unicode_or("█", "#").repeat(bar_len),
+                value_style = value_style(self.theme),
+                count = count,
+            ));
+        }
+
+        lines
+    }
+}
+
+fn stat_line<T>(theme: &Theme, label: &str, value: T) -> String
+where
+    T: fmt::Display,
+{
+    format!(
+        "{label_style}{label:<16}{value_style}{value}",
+        label_style = label_style(theme),
+        label = label,
+        value_style = value_style(theme),
+        value = value,
+    )
+}
+
+fn label_style(theme: &Theme) -> impl fmt::Display {
+    theme.fg(color::White)
+}
+
+fn value_style(theme: &Theme) -> impl fmt::Display {
+    theme.fg(color::Cyan)
+}
@@ -0,0 +1,115 @@
+//! Module for the status bar along the bottom of the board.
+
+use std::{fmt, time::Duration};
+
+use termion::color;
+
+use crate::{
+    model::{
+        keys::{Command, Keymap},
+        Game,
+    },
+    utils::str::CharacterLength,
+};
+
+use super::{geometry, help::key_label, theme::Theme, Widget};
+
+/// How much space to leave between the items in the bar
+static SEPARATOR: &str = "   ";
+
+/// Shows the score and any Vegas bankroll on the left, and the time, moves, stock passes, and deal
+/// seed on the right. The whole row is written every time, so nothing stale is left behind.
+#[derive(Debug)]
+pub struct StatusBarWidget<'a> {
+    pub bounds: geometry::Rect<u16>,
+    pub game: &'a Game,
+    /// The Vegas bankroll, including the game in progress, if playing Vegas
+    pub bankroll: Option<i64>,
+    /// The keys for playing, to say which key auto-completes
+    pub keymap: Option<&'a Keymap>,
+    pub theme: &'a Theme,
+}
+
+impl<'a> Widget for StatusBarWidget<'a> {
+    fn bounds(&self) -> geometry::Rect<u16> {
+        self.bounds
+    }
+}
+
+impl<'a> fmt::Display for StatusBarWidget<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = usize::from(self.bounds.size.width);
+
+        let mut score_text = format!("Score: {}", self.game.score.total());
+        if let Some(bankroll) = self.bankroll {
+            score_text.push_str(&format!("{}Bankroll: {}", SEPARATOR, bankroll));
+        }
+
+        let progress_text = self.progress_text();
+
+        // Offer to auto-complete the game once it's as good as won, but only if there's a key for
+        // it and there's room.
+        let mut prompt_text = self
+            .auto_complete_key()
+            .map(|key| format!("{}Press {} to auto-complete", SEPARATOR, key))
+            .unwrap_or_default();
+        let full_len = score_text.char_len() + prompt_text.char_len() + progress_text.char_len();
+        if !self.game.can_auto_complete() || full_len > width {
+            prompt_text.clear();
+        }
+
+        let len = score_text.char_len() + prompt_text.char_len() + progress_text.char_len();
+        let padding = " ".repeat(width.saturating_sub(len));
+
+        write!(
+            fmt,
+            "{}{}{}{}{}{}{}{}",
+            geometry::goto(self.bounds.origin),
+            self.theme.fg(color::White),
+            score_text,
+            self.theme.fg(color::LightYellow),
+            prompt_text,
+            padding,
+            self.theme.fg(color::LightBlack),
+            progress_text,
+        )
+    }
+}
+
+impl<'a> StatusBarWidget<'a> {
+    fn auto_complete_key(&self) -> Option<String> {
+        let key = *self.keymap?.keys_for(Command::AutoComplete).first()?;
+        Some(key_label(key, self.theme))
+    }
+
+    fn progress_text(&self) -> String {
+        let mut items = vec![
+            format!("Time {}", format_duration(self.game.elapsed())),
+            format!("Moves {}", self.game.move_count),
+        ];
+
+        let stock_pass = self.game.stock_pass();
+        items.push(match self.game.stock_pass_limit() {
+            Some(limit) => format!("Pass {}/{}", stock_pass, limit),
+            None => format!("Pass {}", stock_pass),
+        });
+
+        if let Some(seed) = self.game.seed {
+            items.push(format!("Deal {}", seed));
+        }
+
+        items.join(SEPARATOR)
+    }
+}
+
+/// Format a duration as minutes and seconds, with hours in front once there are any.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
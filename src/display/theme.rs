@@ -0,0 +1,457 @@
+//! Module for how the display looks, as chosen in the display settings.
+//!
+//! A theme sets the colours and patterns the widgets draw with. Themes are read from TOML files in
+//! the `themes` directory next to the settings file, or next to the file given with `--config`,
+//! e.g., `themes/ocean.toml` for a theme called `ocean`, or else picked from the
+//! [built-in themes](BUILTIN_THEME_NAMES).
+
+use config::{Config, ConfigError, File, FileFormat};
+use serde::Deserialize;
+use snafu::{OptionExt, ResultExt};
+use std::{
+    convert::TryFrom,
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use termion::color;
+
+use crate::model::{settings::DisplaySettings, Color, Suit};
+
+use super::{
+    card::{ASCII_CARD_FRAME_STYLE, CARD_FRAME_STYLE, SQUARE_CARD_FRAME_STYLE},
+    frame::{self, FrameStyle},
+};
+
+/// The directory under the config directory where theme files are kept
+static THEMES_DIR: &str = "themes";
+
+/// The name of every built-in theme, as written in the settings.
+pub static BUILTIN_THEME_NAMES: &[&str] = &["classic", "four-color", "felt"];
+
+/// The width of the pattern on a card back
+static CARD_BACK_WIDTH: usize = 4;
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Unable to read theme {:?}: {}", name, source))]
+    ReadError { name: String, source: ConfigError },
+
+    #[snafu(display(
+        "Unknown theme: {:?}, expected a theme file or one of {}",
+        name,
+        BUILTIN_THEME_NAMES.join(", ")
+    ))]
+    UnknownTheme { name: String },
+
+    #[snafu(display("Unknown color: {:?}", name))]
+    InvalidColor { name: String },
+}
+
+pub type Result<T, E = Error> = ::std::result::Result<T, E>;
+
+/// A colour as written in a theme file.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum ThemeColor {
+    /// Whatever colour the terminal uses when none is set
+    Default,
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    LightBlack,
+    LightRed,
+    LightGreen,
+    LightYellow,
+    LightBlue,
+    LightMagenta,
+    LightCyan,
+    LightWhite,
+    /// One of the 256 colours of the terminal's palette, written as its number
+    Ansi(u8),
+    /// A true colour, written as `#rrggbb`
+    Rgb(u8, u8, u8),
+}
+
+/// The name of every colour that isn't a palette number or true colour, as written in theme files.
+static COLOR_NAMES: &[(&str, ThemeColor)] = &[
+    ("default", ThemeColor::Default),
+    ("black", ThemeColor::Black),
+    ("red", ThemeColor::Red),
+    ("green", ThemeColor::Green),
+    ("yellow", ThemeColor::Yellow),
+    ("blue", ThemeColor::Blue),
+    ("magenta", ThemeColor::Magenta),
+    ("cyan", ThemeColor::Cyan),
+    ("white", ThemeColor::White),
+    ("light-black", ThemeColor::LightBlack),
+    ("light-red", ThemeColor::LightRed),
+    ("light-green", ThemeColor::LightGreen),
+    ("light-yellow", ThemeColor::LightYellow),
+    ("light-blue", ThemeColor::LightBlue),
+    ("light-magenta", ThemeColor::LightMagenta),
+    ("light-cyan", ThemeColor::LightCyan),
+    ("light-white", ThemeColor::LightWhite),
+];
+
+impl FromStr for ThemeColor {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        let lowercase_name = name.to_lowercase();
+
+        if let Some(&(_, color)) = COLOR_NAMES
+            .iter()
+            .find(|(other, _)| *other == lowercase_name)
+        {
+            return Ok(color);
+        }
+
+        let rgb = |hex: &str| {
+            if hex.len() != 6 {
+                return None;
+            }
+
+            let component = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+            Some(ThemeColor::Rgb(component(0)?, component(2)?, component(4)?))
+        };
+
+        lowercase_name
+            .strip_prefix('#')
+            .and_then(rgb)
+            .or_else(|| lowercase_name.parse().ok().map(ThemeColor::Ansi))
+            .context(InvalidColor { name })
+    }
+}
+
+impl TryFrom<String> for ThemeColor {
+    type Error = Error;
+
+    fn try_from(name: String) -> Result<Self> {
+        name.parse()
+    }
+}
+
+impl ThemeColor {
+    /// Write the matching termion colour with the given function.
+    fn write_with<F>(self, write: F) -> fmt::Result
+    where
+        F: FnOnce(&dyn color::Color) -> fmt::Result,
+    {
+        match self {
+            ThemeColor::Default => write(&color::Reset),
+            ThemeColor::Black => write(&color::Black),
+            ThemeColor::Red => write(&color::Red),
+            ThemeColor::Green => write(&color::Green),
+            ThemeColor::Yellow => write(&color::Yellow),
+            ThemeColor::Blue => write(&color::Blue),
+            ThemeColor::Magenta => write(&color::Magenta),
+            ThemeColor::Cyan => write(&color::Cyan),
+            ThemeColor::White => write(&color::White),
+            ThemeColor::LightBlack => write(&color::LightBlack),
+            ThemeColor::LightRed => write(&color::LightRed),
+            ThemeColor::LightGreen => write(&color::LightGreen),
+            ThemeColor::LightYellow => write(&color::LightYellow),
+            ThemeColor::LightBlue => write(&color::LightBlue),
+            ThemeColor::LightMagenta => write(&color::LightMagenta),
+            ThemeColor::LightCyan => write(&color::LightCyan),
+            ThemeColor::LightWhite => write(&color::LightWhite),
+            ThemeColor::Ansi(value) => write(&color::AnsiValue(value)),
+            ThemeColor::Rgb(red, green, blue) => write(&color::Rgb(red, green, blue)),
+        }
+    }
+}
+
+impl color::Color for ThemeColor {
+    fn write_fg(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_with(|color| color.write_fg(fmt))
+    }
+
+    fn write_bg(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_with(|color| color.write_bg(fmt))
+    }
+}
+
+/// The frame drawn around each card.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize)]
+pub enum CardFrame {
+    Rounded,
+    Square,
+}
+
+/// The frame drawn around the help, the statistics, and the win message.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize)]
+pub enum DialogFrame {
+    Single,
+    Double,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct SuitColors {
+    pub spades: ThemeColor,
+    pub hearts: ThemeColor,
+    pub diamonds: ThemeColor,
+    pub clubs: ThemeColor,
+}
+
+impl Default for SuitColors {
+    fn default() -> Self {
+        SuitColors {
+            spades: ThemeColor::Default,
+            hearts: ThemeColor::Red,
+            diamonds: ThemeColor::Red,
+            clubs: ThemeColor::Default,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct CardBack {
+    /// The characters to fill the back with, repeated as needed
+    pub pattern: String,
+    pub color: ThemeColor,
+}
+
+impl Default for CardBack {
+    fn default() -> Self {
+        CardBack {
+            pattern: "░".to_string(),
+            color: ThemeColor::LightBlue,
+        }
+    }
+}
+
+/// The colours and patterns of a theme, as written in a theme file. Anything a theme file leaves
+/// out is taken from the classic theme.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct ThemeStyle {
+    pub suits: SuitColors,
+    pub card_back: CardBack,
+    pub card_frame: CardFrame,
+    pub dialog_frame: DialogFrame,
+    /// The colour of the marker beside the selected cards
+    pub selection: ThemeColor,
+    /// The colour of the marker beside the cards of a hint
+    pub hint: ThemeColor,
+    /// The colour behind everything
+    pub background: ThemeColor,
+}
+
+impl Default for ThemeStyle {
+    fn default() -> Self {
+        ThemeStyle {
+            suits: SuitColors::default(),
+            card_back: CardBack::default(),
+            card_frame: CardFrame::Rounded,
+            dialog_frame: DialogFrame::Double,
+            selection: ThemeColor::LightWhite,
+            hint: ThemeColor::LightYellow,
+            background: ThemeColor::Default,
+        }
+    }
+}
+
+impl ThemeStyle {
+    /// One of the built-in themes, by name.
+    pub fn builtin(name: &str) -> Option<ThemeStyle> {
+        match name {
+            "classic" => Some(ThemeStyle::default()),
+            // Every suit in a colour of its own, so diamonds and hearts can't be mixed up.
+            "four-color" => Some(ThemeStyle {
+                suits: SuitColors {
+                    spades: ThemeColor::Default,
+                    hearts: ThemeColor::Red,
+                    diamonds: ThemeColor::Blue,
+                    clubs: ThemeColor::Green,
+                },
+                ..ThemeStyle::default()
+            }),
+            "felt" => Some(ThemeStyle {
+                card_back: CardBack {
+                    pattern: "▚▞".to_string(),
+                    color: ThemeColor::LightRed,
+                },
+                card_frame: CardFrame::Square,
+                dialog_frame: DialogFrame::Single,
+                background: ThemeColor::Green,
+                ..ThemeStyle::default()
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn read_from_file(path: &Path) -> Result<ThemeStyle, ConfigError> {
+        let mut config = Config::new();
+        config.merge(File::from(path).format(FileFormat::Toml))?;
+        config.try_into()
+    }
+}
+
+/// Where the theme file with the given name would be, in the themes directory beside the settings
+/// file in the given directory.
+pub fn theme_file_path(name: &str, config_dir: &Path) -> PathBuf {
+    let mut path = config_dir.to_path_buf();
+    path.push(THEMES_DIR);
+    path.push(format!("{}.toml", name));
+    path
+}
+
+/// How the widgets should draw themselves. Without colour, no colour escapes are written at all,
+/// so the display works on monochrome terminals and in log captures. Without Unicode, everything is
+/// drawn in plain ASCII, for serial consoles and limited fonts.
+#[derive(Clone, Debug)]
+pub struct Theme {
+    pub color: bool,
+    pub unicode: bool,
+    pub style: ThemeStyle,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            color: true,
+            unicode: true,
+            style: ThemeStyle::default(),
+        }
+    }
+}
+
+impl Theme {
+    /// Load the theme named in the settings: from its theme file beside the settings file, if there
+    /// is one, or else one of the built-in themes.
+    pub fn load(settings: &DisplaySettings, config_dir: Option<&Path>) -> Result<Theme> {
+        let name = settings.theme.as_str();
+
+        let style = match config_dir.map(|config_dir| theme_file_path(name, config_dir)) {
+            Some(path) if path.exists() => {
+                ThemeStyle::read_from_file(&path).context(ReadError { name })?
+            }
+            _ => ThemeStyle::builtin(name).context(UnknownTheme { name })?,
+        };
+
+        Ok(Theme {
+            color: settings.color,
+            unicode: settings.unicode,
+            style,
+        })
+    }
+
+    /// Switch to the given foreground colour, or do nothing if drawing without colour.
+    pub fn fg<C>(&self, color: C) -> ColorEscape<color::Fg<C>>
+    where
+        C: color::Color,
+    {
+        ColorEscape(Some(color::Fg(color)).filter(|_| self.color))
+    }
+
+    /// Switch to the given background colour, or do nothing if drawing without colour.
+    pub fn bg<C>(&self, color: C) -> ColorEscape<color::Bg<C>>
+    where
+        C: color::Color,
+    {
+        ColorEscape(Some(color::Bg(color)).filter(|_| self.color))
+    }
+
+    /// Pick what to draw, e.g., a frame style, depending on whether Unicode can be used.
+    pub fn unicode_or<T>(&self, unicode: T, ascii: T) -> T {
+        if self.unicode {
+            unicode
+        } else {
+            ascii
+        }
+    }
+
+    pub fn suit_color(&self, suit: Suit) -> ThemeColor {
+        match suit {
+            Suit::Spades => self.style.suits.spades,
+            Suit::Hearts => self.style.suits.hearts,
+            Suit::Diamonds => self.style.suits.diamonds,
+            Suit::Clubs => self.style.suits.clubs,
+        }
+    }
+
+    /// The symbol to draw for a suit. Without colour, the red suits are drawn in outline so they
+    /// can still be told apart from the black suits. Without Unicode, suits go by their initials.
+    pub fn suit_symbol(&self, suit: Suit) -> &'static str {
+        if !self.unicode {
+            return match suit {
+                Suit::Spades => "S",
+                Suit::Hearts => "H",
+                Suit::Diamonds => "D",
+                Suit::Clubs => "C",
+            };
+        }
+
+        let outline = !self.color && suit.color() == Color::Red;
+
+        match suit {
+            Suit::Spades => "♠",
+            Suit::Clubs => "♣",
+            Suit::Hearts if outline => "♡",
+            Suit::Diamonds if outline => "♢",
+            Suit::Hearts => "♥",
+            Suit::Diamonds => "♦",
+        }
+    }
+
+    /// Redraw the suits in text from the model, e.g., a message naming a card, the way this theme
+    /// draws them.
+    pub fn suits_in(&self, text: &str) -> String {
+        Suit::values().fold(text.to_string(), |text, suit| {
+            text.replace(&suit.to_string(), self.suit_symbol(suit))
+        })
+    }
+
+    /// One row of the pattern on a card back. Without Unicode, a pattern that isn't plain ASCII is
+    /// drawn with `#` instead.
+    pub fn card_back(&self) -> String {
+        let pattern = self.style.card_back.pattern.as_str();
+        let pattern = if self.unicode || pattern.is_ascii() {
+            pattern
+        } else {
+            "#"
+        };
+
+        pattern.chars().cycle().take(CARD_BACK_WIDTH).collect()
+    }
+
+    pub fn card_frame_style(&self) -> &'static FrameStyle {
+        match self.style.card_frame {
+            _ if !self.unicode => &ASCII_CARD_FRAME_STYLE,
+            CardFrame::Rounded => &CARD_FRAME_STYLE,
+            CardFrame::Square => &SQUARE_CARD_FRAME_STYLE,
+        }
+    }
+
+    pub fn dialog_frame_style(&self) -> &'static FrameStyle {
+        match self.style.dialog_frame {
+            DialogFrame::Single => self.unicode_or(&frame::SINGLE, &frame::ASCII_SINGLE),
+            DialogFrame::Double => self.unicode_or(&frame::DOUBLE, &frame::ASCII_DOUBLE),
+        }
+    }
+}
+
+/// A colour that may not be written, from [`Theme::fg`] or [`Theme::bg`].
+#[derive(Copy, Clone, Debug)]
+pub struct ColorEscape<T>(Option<T>);
+
+impl<T> fmt::Display for ColorEscape<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(ref escape) = self.0 {
+            write!(fmt, "{}", escape)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,22 +1,30 @@
 use std::fmt;
 
+use crate::model::keys::{Command, Keymap};
+
 use super::{
-    frame::{self, FrameWidget, Title},
-    geometry, Widget,
+    frame::{FrameWidget, Title},
+    geometry,
+    help::key_label,
+    theme::Theme,
+    Widget,
 };
 
 lazy_static! {
-    static ref CONTENT_SIZE: geometry::Size2D<u16> = geometry::size2(20, 3);
+    static ref CONTENT_SIZE: geometry::Size2D<u16> = geometry::size2(20, 5);
     static ref BORDER: geometry::SideOffsets2D<u16> = geometry::SideOffsets2D::new_all_same(1);
     static ref PADDING: geometry::SideOffsets2D<u16> = geometry::SideOffsets2D::new(1, 2, 1, 2);
 }
 
 #[derive(Debug)]
-pub struct WinWidget {
+pub struct WinWidget<'a> {
     pub bounds: geometry::Rect<u16>,
+    /// The keys for the win message, to offer what the player can do next
+    pub keymap: &'a Keymap,
+    pub theme: &'a Theme,
 }
 
-impl Widget for WinWidget {
+impl<'a> Widget for WinWidget<'a> {
     fn bounds(&self) -> geometry::Rect<u16> {
         let left_offset = self.bounds.size.width.saturating_sub(CONTENT_SIZE.width) / 2;
         let top_offset = self.bounds.size.height.saturating_sub(CONTENT_SIZE.height) / 2;
@@ -29,7 +37,7 @@ impl Widget for WinWidget {
     }
 }
 
-impl fmt::Display for WinWidget {
+impl<'a> fmt::Display for WinWidget<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         let frame_bounds = self.bounds();
         let inner_bounds = frame_bounds.inner_rect(*BORDER + *PADDING);
@@ -38,17 +46,47 @@ impl fmt::Display for WinWidget {
             bounds: frame_bounds,
             top_title: Some(Title::center("Y O U   W I N !")),
             bottom_title: None,
-            frame_style: &frame::DOUBLE,
+            frame_style: self.theme.dialog_frame_style(),
+            theme: self.theme,
         };
 
         write!(fmt, "{}", frame_display)?;
 
         let goto_line1 = geometry::goto(inner_bounds.origin);
         let goto_line2 = geometry::goto(inner_bounds.origin + geometry::vec2(0, 2));
+        let goto_line3 = geometry::goto(inner_bounds.origin + geometry::vec2(0, 3));
+        let goto_line4 = geometry::goto(inner_bounds.origin + geometry::vec2(0, 4));
 
         write!(fmt, "{}Congratulations!", goto_line1)?;
-        write!(fmt, "{}New Game? (Y/N)", goto_line2)?;
+
+        // Declining a new game quits, so its key goes alongside.
+        let new_game_keys = self
+            .key_label(Command::NewGame)
+            .into_iter()
+            .chain(self.key_label(Command::Quit))
+            .collect::<Vec<_>>();
+        if !new_game_keys.is_empty() {
+            write!(fmt, "{}New Game? ({})", goto_line2, new_game_keys.join("/"))?;
+        }
+
+        if let Some(key) = self.key_label(Command::ReplayGame) {
+            write!(fmt, "{}Replay Deal? ({})", goto_line3, key)?;
+        }
+
+        if let Some(key) = self.key_label(Command::Statistics) {
+            write!(fmt, "{}Statistics? ({})", goto_line4, key)?;
+        }
 
         Ok(())
     }
 }
+
+impl<'a> WinWidget<'a> {
+    /// The first key for the command, if it has any.
+    fn key_label(&self, command: Command) -> Option<String> {
+        self.keymap
+            .keys_for(command)
+            .first()
+            .map(|&key| key_label(key, self.theme))
+    }
+}
@@ -1,19 +1,26 @@
 //! Module tying together the Klondike model and display.
 
+use log::warn;
 use snafu::ResultExt;
-use std::{collections::HashMap, fmt, io};
+use std::{collections::HashMap, fmt, io, thread, time::Duration};
 use termion::event::Key;
 
 use crate::{
     display::{
         game::{GameWidget, GameWidgetState},
-        geometry, terminal_bounds, DisplayState,
+        geometry,
+        help::{HelpScroll, HelpWidget},
+        terminal_bounds,
+        theme::Theme,
+        DisplayState,
     },
     model::{
         area::AreaId,
         dealer::{create_dealer, Dealer},
         game::{Action, Game},
+        keys::{Command, Keymap},
         settings::GameSettings,
+        stats::{self, GameRecord, Statistics},
     },
     utils::tuple::both,
 };
@@ -29,13 +36,39 @@ pub enum Error {
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// How long to pause after each card when auto-completing, so the player can watch it happen.
+static AUTO_COMPLETE_DELAY: Duration = Duration::from_millis(40);
+
+/// How long to wait for a key before redrawing the status bar anyway, so that its clock keeps up.
+pub static REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Update {
     Action(Action),
     NewGame,
+    /// Deal the current game again from the start, using the same seed.
+    ReplayGame,
+    /// Play the rest of the cards to the foundations, if the game is as good as won.
+    AutoComplete,
+    /// Scroll the help, if it doesn't all fit.
+    ScrollHelp(HelpScroll),
     State(DisplayState),
 }
 
+impl From<Command> for Update {
+    fn from(command: Command) -> Self {
+        match command {
+            Command::Action(action) => Update::Action(action),
+            Command::NewGame => Update::NewGame,
+            Command::ReplayGame => Update::ReplayGame,
+            Command::AutoComplete => Update::AutoComplete,
+            Command::Help => Update::State(DisplayState::HelpMessageOpen),
+            Command::Statistics => Update::State(DisplayState::StatisticsOpen),
+            Command::Quit => Update::State(DisplayState::Quitting),
+        }
+    }
+}
+
 pub trait InputMapper {
     fn map_input(&mut self, input: Key) -> Option<Update>;
 }
@@ -49,16 +82,56 @@ where
     }
 }
 
+/// Maps each key to whatever it's bound to in a [keymap](Keymap), ignoring any other keys.
+#[derive(Debug)]
+pub struct KeymapInputMapper {
+    updates: HashMap<Key, Update>,
+}
+
+impl KeymapInputMapper {
+    pub fn new(keymap: &Keymap) -> Self {
+        let updates = keymap
+            .bindings()
+            .iter()
+            .flat_map(|binding| {
+                let update = Update::from(binding.command);
+                binding.keys.iter().map(move |&key| (key, update))
+            })
+            .collect();
+
+        KeymapInputMapper { updates }
+    }
+}
+
+impl InputMapper for KeymapInputMapper {
+    fn map_input(&mut self, input: Key) -> Option<Update> {
+        self.updates.get(&input).copied()
+    }
+}
+
 pub struct GameEngine<'a, I, O>
 where
-    I: Iterator<Item = Result<Key, io::Error>> + 'a,
+    I: Iterator<Item = Result<Option<Key>, io::Error>> + 'a,
     O: io::Write + 'a,
 {
     settings: &'a GameSettings,
     dealer: Box<dyn Dealer>,
+    /// The seed for the next deal, if the player asked for a particular one. Later deals are left
+    /// to the dealer.
+    next_seed: Option<u64>,
     game: Option<Game>,
+    /// The Vegas bankroll, not counting the game in progress. `None` unless playing Vegas.
+    bankroll: Option<i64>,
+    /// Every game played so far, including the ones played this session
+    statistics: Statistics,
+    /// Whether the game in progress is already in the statistics, because it has been won
+    game_recorded: bool,
     state: DisplayState,
     input_mappers: HashMap<DisplayState, Box<dyn InputMapper + 'a>>,
+    /// The keymaps behind the input mappers built from them, so the help can list their keys
+    keymaps: HashMap<DisplayState, Keymap>,
+    help_scroll: usize,
+    theme: Theme,
     input: I,
     output: O,
     game_widget_state: GameWidgetState,
@@ -66,48 +139,70 @@ where
 
 impl<'a, I, O> GameEngine<'a, I, O>
 where
-    I: Iterator<Item = Result<Key, io::Error>> + 'a,
+    I: Iterator<Item = Result<Option<Key>, io::Error>> + 'a,
     O: io::Write + 'a,
 {
     pub fn tick(&mut self) -> Result<bool> {
         if self.game.is_none() {
-            self.game = Some(self.dealer.deal_game(self.settings));
-            // Refresh to display the initial game state before getting input.
+            let seed = self.next_seed.take().or_else(|| self.dealer.new_seed());
+            self.deal_game(seed)?;
+        }
+
+        let input = self.input.next().transpose().context(IoError)?;
+
+        // No key was pressed in time, so just bring the clock in the status bar up to date.
+        if input == Some(None) && self.state == DisplayState::Playing {
             self.refresh(&[])?;
         }
 
-        let update = both(
-            self.input.next().transpose().context(IoError)?,
-            self.input_mappers.get_mut(&self.state),
-        )
-        .and_then(|(input, input_mapper)| input_mapper.map_input(input));
+        let update = both(input.flatten(), self.input_mappers.get_mut(&self.state))
+            .and_then(|(input, input_mapper)| input_mapper.map_input(input));
 
         if let Some(update) = update {
             let area_ids = match update {
                 Update::Action(action) => {
                     if let Some(ref mut game) = self.game {
                         let area_ids = game.apply_action(action);
-
-                        if game.is_win() {
-                            // Refresh first to display the winning game state.
-                            self.refresh(&area_ids)?;
-                            self.state = DisplayState::WinMessageOpen;
-                        }
-
-                        area_ids
+                        self.check_for_win(area_ids)?
                     } else {
                         vec![]
                     }
                 }
+                Update::AutoComplete => {
+                    self.auto_complete()?;
+                    self.check_for_win(vec![])?
+                }
                 Update::NewGame => {
-                    self.game = None;
-                    self.state = DisplayState::Playing;
+                    self.settle_game();
+                    self.set_state(DisplayState::Playing);
                     vec![]
                 }
-                Update::State(state) => {
-                    self.state = state;
+                Update::ReplayGame => {
+                    let seed = self.game.as_ref().and_then(|game| game.seed);
+                    self.settle_game();
+                    self.set_state(DisplayState::Playing);
+                    self.deal_game(seed)?;
+                    vec![]
+                }
+                Update::ScrollHelp(scroll) => {
+                    self.scroll_help(scroll)?;
                     vec![]
                 }
+                Update::State(state) => {
+                    self.set_state(state);
+
+                    if state == DisplayState::HelpMessageOpen {
+                        self.help_scroll = 0;
+                    }
+
+                    // Going back to a game that's been won, e.g., after looking at the statistics
+                    // from the win message, brings the win message back up.
+                    if state == DisplayState::Playing {
+                        self.check_for_win(vec![])?
+                    } else {
+                        vec![]
+                    }
+                }
             };
 
             self.refresh(&area_ids)?;
@@ -116,6 +211,131 @@ where
         Ok(self.state != DisplayState::Quitting)
     }
 
+    pub fn game(&self) -> Option<&Game> {
+        self.game.as_ref()
+    }
+
+    /// The Vegas bankroll, not counting the game in progress, if playing Vegas.
+    pub fn bankroll(&self) -> Option<i64> {
+        self.bankroll
+    }
+
+    pub fn statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    /// Be done with the current game, adding what it won or lost to the bankroll. A won game was
+    /// recorded in the statistics as soon as it was won, so this only records abandoned games.
+    pub fn settle_game(&mut self) {
+        self.record_game();
+
+        if let Some(game) = self.game.take() {
+            if let Some(ref mut bankroll) = self.bankroll {
+                *bankroll += i64::from(game.score.total());
+            }
+        }
+
+        self.game_recorded = false;
+    }
+
+    /// Record the current game in the statistics, unless it has been recorded already.
+    fn record_game(&mut self) {
+        if self.game_recorded {
+            return;
+        }
+
+        // Every deal counts, even one that's given up on without a single move, so that dealing
+        // again can't be used to skip past bad deals.
+        if let Some(ref game) = self.game {
+            let record = GameRecord::of(game, self.settings);
+
+            if let Err(error) = stats::append_record(&record) {
+                warn!("Unable to record game: {}", error);
+            }
+
+            self.statistics.record(record);
+            self.game_recorded = true;
+        }
+    }
+
+    /// Change what's on display. The game's clock only runs while it's being played.
+    fn set_state(&mut self, state: DisplayState) {
+        self.state = state;
+
+        if let Some(ref mut game) = self.game {
+            if state == DisplayState::Playing {
+                game.resume();
+            } else {
+                game.pause();
+            }
+        }
+    }
+
+    /// Open the win message if the game has been won, refreshing first to display the winning game
+    /// state. The win is recorded right away, so that the statistics include it even before the
+    /// next game is dealt. Returns the area ids that still need refreshing.
+    fn check_for_win(&mut self, area_ids: Vec<AreaId>) -> Result<Vec<AreaId>> {
+        if self.game.as_ref().map(Game::is_win).unwrap_or_default() {
+            self.record_game();
+            self.refresh(&area_ids)?;
+            self.set_state(DisplayState::WinMessageOpen);
+        }
+
+        Ok(area_ids)
+    }
+
+    /// Play cards to the foundations one at a time, refreshing after each so the player can see
+    /// them move.
+    fn auto_complete(&mut self) -> Result<()> {
+        if !self
+            .game
+            .as_ref()
+            .map(Game::can_auto_complete)
+            .unwrap_or_default()
+        {
+            return Ok(());
+        }
+
+        while let Some(area_ids) = self
+            .game
+            .as_mut()
+            .map(Game::auto_complete_step)
+            .filter(|area_ids| !area_ids.is_empty())
+        {
+            self.refresh(&area_ids)?;
+            thread::sleep(AUTO_COMPLETE_DELAY);
+        }
+
+        Ok(())
+    }
+
+    fn deal_game(&mut self, seed: Option<u64>) -> Result<()> {
+        self.game = Some(self.dealer.deal_game(seed, self.settings));
+
+        // Forget what we drew for the last game, so that the whole screen is redrawn.
+        self.game_widget_state = GameWidgetState::default();
+
+        // Refresh to display the initial game state before getting input.
+        self.refresh(&[])
+    }
+
+    fn scroll_help(&mut self, scroll: HelpScroll) -> Result<()> {
+        if let (Some(game), Some(keymap)) = (&self.game, self.keymaps.get(&DisplayState::Playing)) {
+            let terminal_size = terminal_bounds().context(IoError)?;
+
+            let widget = HelpWidget {
+                bounds: geometry::Rect::from_size(terminal_size),
+                keymap,
+                area_ids: &game.area_ids(),
+                scroll: self.help_scroll,
+                theme: &self.theme,
+            };
+            self.help_scroll = widget.scrolled(scroll);
+        }
+
+        Ok(())
+    }
+
     fn refresh(&mut self, area_ids: &[AreaId]) -> Result<()> {
         if let Some(ref game) = self.game {
             let terminal_size = terminal_bounds().context(IoError)?;
@@ -124,6 +344,14 @@ where
                 area_ids,
                 bounds: geometry::Rect::from_size(terminal_size),
                 game,
+                bankroll: self
+                    .bankroll
+                    .map(|bankroll| bankroll + i64::from(game.score.total())),
+                statistics: &self.statistics,
+                keymap: self.keymaps.get(&DisplayState::Playing),
+                win_keymap: self.keymaps.get(&DisplayState::WinMessageOpen),
+                help_scroll: self.help_scroll,
+                theme: &self.theme,
                 display_state: self.state,
                 widget_state: &self.game_widget_state,
             };
@@ -137,7 +365,7 @@ where
 
 impl<'a, I, O> fmt::Debug for GameEngine<'a, I, O>
 where
-    I: Iterator<Item = Result<Key, io::Error>> + 'a,
+    I: Iterator<Item = Result<Option<Key>, io::Error>> + 'a,
     O: io::Write + 'a,
 {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -150,26 +378,43 @@ where
 
 pub struct GameEngineBuilder<'a, I, O>
 where
-    I: IntoIterator<Item = Result<Key, io::Error>> + 'a,
+    I: IntoIterator<Item = Result<Option<Key>, io::Error>> + 'a,
     O: io::Write + 'a,
 {
     settings: &'a GameSettings,
+    seed: Option<u64>,
+    game: Option<Game>,
+    abandoned_game: Option<Game>,
+    bankroll: Option<i64>,
+    statistics: Statistics,
     state: DisplayState,
     input_mappers: HashMap<DisplayState, Box<dyn InputMapper + 'a>>,
+    keymaps: HashMap<DisplayState, Keymap>,
+    theme: Theme,
     input: I,
     output: O,
 }
 
 impl<'a, I, O> GameEngineBuilder<'a, I, O>
 where
-    I: IntoIterator<Item = Result<Key, io::Error>> + 'a,
+    I: IntoIterator<Item = Result<Option<Key>, io::Error>> + 'a,
     O: io::Write + 'a,
 {
+    /// Build an engine that reads keys from `input` and draws to `output`. Each input item is
+    /// either the next key pressed, or `None` if no key was pressed within about
+    /// [`REFRESH_INTERVAL`], so that the display can be kept up to date in the meantime.
     pub fn builder(settings: &'a GameSettings, input: I, output: O) -> Self {
         GameEngineBuilder {
             settings,
+            seed: None,
+            game: None,
+            abandoned_game: None,
+            bankroll: None,
+            statistics: Statistics::default(),
             state: DisplayState::Playing,
             input_mappers: HashMap::new(),
+            keymaps: HashMap::new(),
+            theme: Theme::default(),
             input,
             output,
         }
@@ -183,26 +428,93 @@ where
         self
     }
 
+    /// Map input with the keymap in the given state. The keymap for playing is also listed in the
+    /// help.
+    pub fn keymap(mut self, state: DisplayState, keymap: Keymap) -> Self {
+        let _ = self
+            .input_mappers
+            .insert(state, Box::new(KeymapInputMapper::new(&keymap)));
+        let _ = self.keymaps.insert(state, keymap);
+        self
+    }
+
+    /// Draw the display with the given theme, rather than the classic theme.
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Deal the first game from the given seed, rather than one picked by the dealer. Only the
+    /// first deal is affected, and only if there's no game in progress to play instead.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Start with a game already in progress, e.g., one resumed from a save, instead of dealing a
+    /// new one.
+    pub fn game(mut self, game: Game) -> Self {
+        self.game = Some(game);
+        self
+    }
+
+    /// Give up on a game before starting, e.g., a saved game the player chose not to resume. It's
+    /// settled like any other game the player walks away from.
+    pub fn abandon_game(mut self, game: Game) -> Self {
+        self.abandoned_game = Some(game);
+        self
+    }
+
+    /// Keep a Vegas bankroll, starting from the given balance.
+    pub fn bankroll(mut self, balance: i64) -> Self {
+        self.bankroll = Some(balance);
+        self
+    }
+
+    /// Start from the statistics of earlier sessions, rather than from nothing.
+    pub fn statistics(mut self, statistics: Statistics) -> Self {
+        self.statistics = statistics;
+        self
+    }
+
     pub fn start(self) -> Result<GameEngine<'a, I::IntoIter, O>> {
         let dealer = create_dealer(self.settings.dealer);
         let game_widget_state = GameWidgetState::default();
 
-        Ok(GameEngine {
+        let mut engine = GameEngine {
             settings: self.settings,
             dealer,
-            game: None,
+            next_seed: self.seed,
+            game: self.abandoned_game,
+            bankroll: self.bankroll,
+            statistics: self.statistics,
+            game_recorded: false,
             state: self.state,
             input_mappers: self.input_mappers,
+            keymaps: self.keymaps,
+            help_scroll: 0,
+            theme: self.theme,
             input: self.input.into_iter(),
             output: self.output,
             game_widget_state,
-        })
+        };
+
+        // Settle the abandoned game, if any, before the game to play takes its place.
+        engine.settle_game();
+        engine.game = self.game;
+
+        if engine.game.is_some() {
+            // Refresh to display the resumed game state before getting input.
+            engine.refresh(&[])?;
+        }
+
+        Ok(engine)
     }
 }
 
 impl<'a, I, O> fmt::Debug for GameEngineBuilder<'a, I, O>
 where
-    I: IntoIterator<Item = Result<Key, io::Error>> + 'a,
+    I: IntoIterator<Item = Result<Option<Key>, io::Error>> + 'a,
     O: io::Write + 'a,
 {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
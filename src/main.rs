@@ -1,95 +1,331 @@
-use std::{convert::TryFrom, error::Error, fs};
+use std::{
+    error::Error,
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
 
-use log::{info, LevelFilter};
-use num_traits::ToPrimitive;
+use chrono::NaiveDate;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use log::{info, warn, LevelFilter};
 use simplelog::{ConfigBuilder, WriteLogger};
-use termion::{event::Key, input::TermRead};
+use termion::event::Key;
 
 use klondike_lib::{
-    display::DisplayState,
-    engine::{GameEngineBuilder, Update},
-    model::{game::Action, AreaId, Settings, Suit},
-    terminal::{TtyInput, TtyOutput},
+    display::{help::HelpScroll, theme::Theme, DisplayState},
+    engine::{GameEngineBuilder, Update, REFRESH_INTERVAL},
+    model::{
+        game::Action,
+        keys::{Command, Keymap},
+        save,
+        settings::{config_dir, DealerMode, ScoringMode, SettingsOverrides},
+        stats::{self, ExportFormat, RecordFilter},
+        Settings,
+    },
+    terminal::{TimedKeys, TtyInput, TtyOutput},
 };
 
 static LOG_FILE: &str = "klondike.log";
 
+static LOG_LEVEL: &str = "debug";
+
+static DATE_FORMAT: &str = "%Y-%m-%d";
+
 fn main() -> Result<(), Box<dyn Error>> {
+    let matches = app().get_matches();
+
+    if let Some(stats_matches) = matches.subcommand_matches("stats") {
+        return export_statistics(stats_matches);
+    }
+
+    let log_level: LevelFilter = matches.value_of("log-level").unwrap_or(LOG_LEVEL).parse()?;
+    let log_file = matches.value_of("log-file").unwrap_or(LOG_FILE);
+
     WriteLogger::init(
-        LevelFilter::Debug,
+        log_level,
         ConfigBuilder::new().set_time_to_local(true).build(),
-        fs::File::create(LOG_FILE)?,
+        fs::File::create(log_file)?,
     )?;
     log_panics::init();
 
     info!("STARTING KLONDIKE");
 
-    let input = TtyInput::new()?;
-    let output = TtyOutput::new()?;
+    let overrides = settings_overrides(&matches)?;
+    let settings = Settings::read_from_system(&overrides)?;
+
+    let saved_game = save::load_game(&settings.game).unwrap_or_else(|error| {
+        warn!("Unable to resume saved game: {}", error);
+        None
+    });
+
+    // Asking for a particular deal means starting a new game, too.
+    let new_game = matches.is_present("new") || matches.is_present("seed");
+    if matches.is_present("resume") && saved_game.is_none() {
+        return Err("There's no saved game to resume".into());
+    }
+
+    let input = TimedKeys::new(TtyInput::new()?, REFRESH_INTERVAL);
+    let output = TtyOutput::new(settings.display.color)?;
 
-    let settings = Settings::read_from_system()?;
+    let statistics = stats::load_statistics().unwrap_or_else(|error| {
+        warn!("Unable to load statistics: {}", error);
+        Default::default()
+    });
 
-    let mut engine = GameEngineBuilder::builder(&settings.game, input.keys(), output)
-        .input_mapper(DisplayState::Playing, handle_playing_input)
-        .input_mapper(DisplayState::HelpMessageOpen, handle_help_input)
-        .input_mapper(DisplayState::WinMessageOpen, handle_win_input)
-        .start()?;
+    let playing_keymap = settings.keys.playing_keymap()?;
+    let help_keymap = playing_keymap.clone();
+
+    let mut engine_builder = GameEngineBuilder::builder(&settings.game, input, output)
+        .keymap(DisplayState::Playing, playing_keymap)
+        .input_mapper(DisplayState::HelpMessageOpen, move |key| {
+            handle_help_input(&help_keymap, key)
+        })
+        .input_mapper(DisplayState::StatisticsOpen, handle_statistics_input)
+        .keymap(DisplayState::WinMessageOpen, settings.keys.win_keymap()?)
+        .theme(Theme::load(
+            &settings.display,
+            config_dir(&overrides).as_deref(),
+        )?)
+        .statistics(statistics);
+
+    if let Some(game) = saved_game {
+        engine_builder = if new_game {
+            engine_builder.abandon_game(game)
+        } else {
+            engine_builder.game(game)
+        };
+    }
+
+    if settings.game.scoring == ScoringMode::Vegas {
+        let bankroll = save::load_bankroll().unwrap_or_else(|error| {
+            warn!("Unable to load bankroll: {}", error);
+            0
+        });
+        engine_builder = engine_builder.bankroll(bankroll);
+    }
+
+    if let Some(seed) = matches.value_of("seed") {
+        engine_builder = engine_builder.seed(seed.parse()?);
+    }
+
+    let mut engine = engine_builder.start()?;
 
     while engine.tick()? {}
 
+    match engine.game() {
+        Some(game) if !game.is_win() => save::save_game(game)?,
+        _ => {
+            engine.settle_game();
+            save::delete_saved_game()?
+        }
+    }
+
+    if let Some(bankroll) = engine.bankroll() {
+        save::save_bankroll(bankroll)?;
+    }
+
     info!("QUITTING KLONDIKE");
 
     Ok(())
 }
 
-fn handle_playing_input(key: Key) -> Option<Update> {
-    match key {
-        Key::Char('q') => Some(Update::State(DisplayState::Quitting)),
-        Key::Char('?') => Some(Update::State(DisplayState::HelpMessageOpen)),
+fn app() -> App<'static, 'static> {
+    App::new("klondike")
+        .version(clap::crate_version!())
+        .about(clap::crate_description!())
+        .setting(AppSettings::VersionlessSubcommands)
+        .arg(
+            Arg::with_name("config")
+                .short("c")
+                .long("config")
+                .value_name("FILE")
+                .help("Read settings from this file instead of the usual places")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("seed")
+                .long("seed")
+                .value_name("SEED")
+                .help(
+                    "Start a new game with the deal for this seed, and deal later games at random",
+                )
+                .takes_value(true)
+                .validator(validate_seed)
+                .conflicts_with_all(&["dealer", "resume"]),
+        )
+        .arg(
+            Arg::with_name("dealer")
+                .long("dealer")
+                .value_name("MODE")
+                .help("How to deal each game")
+                .takes_value(true)
+                .possible_values(&["AutoWin", "InOrder", "Random"])
+                .case_insensitive(true),
+        )
+        .arg(
+            Arg::with_name("draw")
+                .short("d")
+                .long("draw")
+                .value_name("COUNT")
+                .help("How many cards to draw from the stock at a time")
+                .takes_value(true)
+                .validator(validate_draw_count),
+        )
+        .arg(
+            Arg::with_name("resume")
+                .short("r")
+                .long("resume")
+                .help("Resume the saved game, failing if there isn't one"),
+        )
+        .arg(
+            Arg::with_name("new")
+                .short("n")
+                .long("new")
+                .help("Start a new game, giving up on any saved game")
+                .conflicts_with("resume"),
+        )
+        .arg(
+            Arg::with_name("log-file")
+                .long("log-file")
+                .value_name("FILE")
+                .help("Where to write the log")
+                .takes_value(true)
+                .default_value(LOG_FILE),
+        )
+        .arg(
+            Arg::with_name("log-level")
+                .long("log-level")
+                .value_name("LEVEL")
+                .help("How much to write to the log")
+                .takes_value(true)
+                .possible_values(&["off", "error", "warn", "info", "debug", "trace"])
+                .case_insensitive(true)
+                .default_value(LOG_LEVEL),
+        )
+        .subcommand(
+            SubCommand::with_name("stats")
+                .about("Export the history of every recorded game")
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .help("How to write the games")
+                        .takes_value(true)
+                        .possible_values(&["csv", "json"])
+                        .default_value("csv"),
+                )
+                .arg(
+                    Arg::with_name("since")
+                        .long("since")
+                        .value_name("DATE")
+                        .help("Only include games played on or after this day, as YYYY-MM-DD")
+                        .takes_value(true)
+                        .validator(validate_date),
+                )
+                .arg(
+                    Arg::with_name("until")
+                        .long("until")
+                        .value_name("DATE")
+                        .help("Only include games played on or before this day, as YYYY-MM-DD")
+                        .takes_value(true)
+                        .validator(validate_date),
+                )
+                .arg(
+                    Arg::with_name("dealer")
+                        .long("dealer")
+                        .value_name("MODE")
+                        .help("Only include games dealt with this dealer mode")
+                        .takes_value(true)
+                        .possible_values(&["AutoWin", "InOrder", "Random", "Seeded"])
+                        .case_insensitive(true),
+                ),
+        )
+}
 
-        Key::Char('s') => Some(Update::Action(Action::MoveTo(AreaId::Stock))),
-        Key::Char('t') => Some(Update::Action(Action::MoveTo(AreaId::Talon))),
+/// Gather the settings given as flags, to be merged over the other settings.
+fn settings_overrides(matches: &ArgMatches) -> Result<SettingsOverrides, Box<dyn Error>> {
+    // Only a shuffling dealer can deal from a seed, so asking for a deal means dealing at random.
+    let dealer = match matches.value_of("dealer") {
+        Some(name) if name.eq_ignore_ascii_case("AutoWin") => Some(DealerMode::AutoWin),
+        Some(name) if name.eq_ignore_ascii_case("InOrder") => Some(DealerMode::InOrder),
+        Some(_) => Some(DealerMode::Random),
+        None if matches.is_present("seed") => Some(DealerMode::Random),
+        None => None,
+    };
 
-        Key::Char('f') => Some(Update::Action(Action::MoveToFoundation)),
+    Ok(SettingsOverrides {
+        config_file: matches.value_of("config").map(PathBuf::from),
+        dealer,
+        draw_from_stock_len: matches.value_of("draw").map(str::parse).transpose()?,
+    })
+}
 
-        Key::Char('h') | Key::Left => Some(Update::Action(Action::MoveLeft)),
-        Key::Char('j') | Key::Down => Some(Update::Action(Action::SelectLess)),
-        Key::Char('k') | Key::Up => Some(Update::Action(Action::SelectMore)),
-        Key::Char('l') | Key::Right => Some(Update::Action(Action::MoveRight)),
+/// Keep seeds to what a seeded dealer in the settings file can hold, which is a signed number.
+fn validate_seed(value: String) -> Result<(), String> {
+    match value.parse::<u64>() {
+        Ok(seed) if seed > i64::MAX as u64 => Err(format!("must be at most {}", i64::MAX)),
+        Ok(_) => Ok(()),
+        Err(error) => Err(error.to_string()),
+    }
+}
 
-        Key::Char(c @ '1'..='9') => {
-            if let Some(index) = c.to_digit(10) {
-                let area_id = AreaId::Tableaux(index.to_u8()? - 1);
-                Some(Update::Action(Action::MoveTo(area_id)))
-            } else {
-                None
-            }
-        }
+fn validate_draw_count(value: String) -> Result<(), String> {
+    match value.parse::<usize>() {
+        Ok(0) => Err("must draw at least one card".to_string()),
+        Ok(_) => Ok(()),
+        Err(error) => Err(error.to_string()),
+    }
+}
 
-        Key::F(i @ 1..=4) => {
-            let area_id = AreaId::Foundation(Suit::try_from(i.to_u8()? - 1).unwrap());
-            Some(Update::Action(Action::MoveTo(area_id)))
-        }
+fn validate_date(value: String) -> Result<(), String> {
+    parse_date(&value)
+        .map(|_| ())
+        .map_err(|error| error.to_string())
+}
+
+fn parse_date(value: &str) -> chrono::ParseResult<NaiveDate> {
+    NaiveDate::parse_from_str(value, DATE_FORMAT)
+}
+
+/// Write the recorded games to standard out, for looking at outside of the game.
+fn export_statistics(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let format = match matches.value_of("format") {
+        Some("json") => ExportFormat::Json,
+        _ => ExportFormat::Csv,
+    };
 
-        Key::Char('-') => Some(Update::Action(Action::MoveBack)),
-        Key::Esc => Some(Update::Action(Action::ReturnHeld)),
+    let filter = RecordFilter {
+        since: matches.value_of("since").map(parse_date).transpose()?,
+        until: matches.value_of("until").map(parse_date).transpose()?,
+        dealer: matches.value_of("dealer").map(String::from),
+    };
 
-        Key::Char(' ') | Key::Char('\n') => Some(Update::Action(Action::Activate)),
+    let statistics = stats::load_statistics()?;
 
-        _ => None,
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    stats::export_records(&statistics, &filter, format, &mut stdout)?;
+
+    if format == ExportFormat::Json {
+        writeln!(stdout)?;
     }
+
+    Ok(())
 }
 
-fn handle_help_input(key: Key) -> Option<Update> {
-    match key {
+/// Scroll the help with the same keys that select more or fewer cards, or with page up and down.
+/// Any other key closes it.
+fn handle_help_input(keymap: &Keymap, key: Key) -> Option<Update> {
+    match (key, keymap.command_for(key)) {
+        (_, Some(Command::Action(Action::SelectMore))) => Some(Update::ScrollHelp(HelpScroll::Up)),
+        (_, Some(Command::Action(Action::SelectLess))) => {
+            Some(Update::ScrollHelp(HelpScroll::Down))
+        }
+        (Key::PageUp, _) => Some(Update::ScrollHelp(HelpScroll::PageUp)),
+        (Key::PageDown, _) => Some(Update::ScrollHelp(HelpScroll::PageDown)),
         _ => Some(Update::State(DisplayState::Playing)),
     }
 }
 
-fn handle_win_input(key: Key) -> Option<Update> {
-    match key {
-        Key::Char('y') => Some(Update::NewGame),
-        Key::Char('n') => Some(Update::State(DisplayState::Quitting)),
-        _ => None,
-    }
+fn handle_statistics_input(_key: Key) -> Option<Update> {
+    Some(Update::State(DisplayState::Playing))
 }
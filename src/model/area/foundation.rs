@@ -1,6 +1,9 @@
+use std::convert::TryFrom;
+
 use crate::{
     model::{
-        card::{Card, Rank, Suit},
+        card::{Card, Suit, SUIT_LEN},
+        rules::{self, Rules},
         settings::GameSettings,
         stack::{Orientation, Stack, StackDetails, StackSelection},
     },
@@ -8,10 +11,15 @@ use crate::{
 };
 
 use super::{
-    Action, Area, AreaId, Held, InvalidCard, MoveResult, NotSupported, NothingToSelect, Result,
-    SelectedArea, SnafuSelectorExt, TooManyCards, UnselectedArea,
+    Action, Area, AreaId, Held, MoveResult, NotSupported, NothingToSelect, Result, SelectedArea,
+    SnafuSelectorExt, UnselectedArea,
 };
 
+/// The suit of the foundation at `index`. Each deck's foundations are in suit order.
+pub fn suit_for_index(index: u8) -> Suit {
+    Suit::try_from(index % SUIT_LEN).unwrap()
+}
+
 /// Selection of a foundation area. Only the top card of a foundation area can be selected, and that
 /// card can either be held (picked up to move) or not. Additionally, depending on settings, it may
 /// not be allowed to move cards out of a foundation area.
@@ -24,12 +32,16 @@ pub struct Selection {
 /// creating piles by suit, starting with aces and ending with kings.
 #[derive(Debug)]
 pub struct Foundation<S> {
-    /// The suit of this foundation. In Klondike there is one foundation for each suit.
+    /// The position of this foundation, counting across the decks
+    index: u8,
+    /// The suit of this foundation. In Klondike there is one foundation for each suit in each deck.
     suit: Suit,
     /// The cards in this area
     cards: Vec<Card>,
     /// Whether the player is allowed to remove cards from this area
     take_from_foundation: bool,
+    /// The rules of the game being played, which decide what cards this area will take
+    rules: &'static dyn Rules,
     /// The current selection state of this foundation area. Expected values are either `()` for
     /// unselected, or a [`Selection`](Selection) instance for selected.
     selection: S,
@@ -45,52 +57,24 @@ pub type SelectedFoundation = Foundation<Selection>;
 
 impl<S> Foundation<S> {
     fn id(&self) -> AreaId {
-        AreaId::Foundation(self.suit)
+        AreaId::Foundation(self.index)
+    }
+
+    fn movable_len(&self) -> usize {
+        if self.take_from_foundation {
+            self.cards.len().min(1)
+        } else {
+            0
+        }
     }
 
     fn validate_cards(&self, held: &Held) -> Result {
         if held.source == self.id() {
             // We'll always take back our own cards.
             Ok(())
-        } else if let [card] = held.cards.as_slice() {
-            ensure!(
-                self.suit == card.suit,
-                InvalidCard {
-                    message: format!("Wrong suit: card: {:?}, suit: {:?}", card, self.suit),
-                }
-            );
-
-            if let Some(foundation_card) = self.cards.last() {
-                // If there are already cards in this foundation, only accept the next card in
-                // sequence.
-                ensure!(
-                    foundation_card.rank.is_followed_by(card.rank),
-                    InvalidCard {
-                        message: format!(
-                            "Card does not follow: card: {:?}, top: {:?}",
-                            card, foundation_card
-                        ),
-                    }
-                );
-                Ok(())
-            } else {
-                // If there are no cards in this foundation yet, we have to start with the ace.
-                ensure!(
-                    card.rank == Rank::Ace,
-                    InvalidCard {
-                        message: format!("Card does not follow: card: {:?}, top: empty", card),
-                    }
-                );
-                Ok(())
-            }
         } else {
-            ensure!(
-                held.cards.is_empty(),
-                TooManyCards {
-                    message: "Expected only one card",
-                }
-            );
-            Ok(())
+            self.rules
+                .foundation_accepts(self.suit, self.cards.last(), &held.cards)
         }
     }
 
@@ -131,9 +115,11 @@ impl<S> Foundation<S> {
 
     fn with_selection<T>(self, selection: T) -> Foundation<T> {
         Foundation {
+            index: self.index,
             suit: self.suit,
             cards: self.cards,
             take_from_foundation: self.take_from_foundation,
+            rules: self.rules,
             selection,
         }
     }
@@ -141,14 +127,17 @@ impl<S> Foundation<S> {
 
 impl UnselectedFoundation {
     pub fn create(
+        index: u8,
         suit: Suit,
         cards: Vec<Card>,
         settings: &GameSettings,
     ) -> Box<dyn UnselectedArea> {
         Box::new(Foundation {
+            index,
             suit,
             cards,
             take_from_foundation: settings.take_from_foundation,
+            rules: rules::rules_for(settings.variant),
             selection: (),
         })
     }
@@ -183,6 +172,26 @@ impl<'a> Area for UnselectedFoundation {
         self.cards.last()
     }
 
+    fn accepts_cards(&self, held: &Held) -> Result {
+        Foundation::validate_cards(self, held)
+    }
+
+    fn movable_len(&self) -> usize {
+        Foundation::movable_len(self)
+    }
+
+    fn spread_len(&self) -> usize {
+        0
+    }
+
+    fn set_spread_len(&mut self, _len: usize) {}
+
+    fn passes_remaining(&self) -> Option<usize> {
+        None
+    }
+
+    fn set_restock_count(&mut self, _count: usize) {}
+
     fn as_stack(&self) -> Stack<'_> {
         self.as_stack(None)
     }
@@ -228,6 +237,26 @@ impl<'a> Area for SelectedFoundation {
         self.cards.last()
     }
 
+    fn accepts_cards(&self, held: &Held) -> Result {
+        Foundation::validate_cards(self, held)
+    }
+
+    fn movable_len(&self) -> usize {
+        Foundation::movable_len(self)
+    }
+
+    fn spread_len(&self) -> usize {
+        0
+    }
+
+    fn set_spread_len(&mut self, _len: usize) {}
+
+    fn passes_remaining(&self) -> Option<usize> {
+        None
+    }
+
+    fn set_restock_count(&mut self, _count: usize) {}
+
     fn as_stack(&self) -> Stack<'_> {
         self.as_stack(Some(self.selection))
     }
@@ -1,8 +1,10 @@
+use serde::{Deserialize, Serialize};
 use snafu;
 use std::{error, fmt};
 
 use super::{
     card::{Card, Suit},
+    settings::GameSettings,
     stack::Stack,
 };
 
@@ -12,9 +14,10 @@ pub mod tableaux;
 pub mod talon;
 
 #[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
 pub enum Error {
-    #[snafu(display("Invalid card: {}", message))]
-    InvalidCard { message: String },
+    #[snafu(display("Invalid card: {}", rejection))]
+    InvalidCard { rejection: Rejection },
 
     #[snafu(display("Too many cards: {}", message))]
     TooManyCards { message: String },
@@ -36,10 +39,97 @@ pub enum Error {
 
     #[snafu(display("Minimum selection"))]
     MinSelection,
+
+    #[snafu(display(
+        "Cannot restock, all passes through the stock are used: maximum: {}",
+        max_passes
+    ))]
+    NoPassesRemain { max_passes: usize },
 }
 
 pub type Result<T = (), E = Error> = ::std::result::Result<T, E>;
 
+/// Why an area turned down a card.
+#[derive(Clone, Debug)]
+pub enum Rejection {
+    /// The card can't go on the top card, or can't go first if there's no top card.
+    DoesNotFollow { card: Card, top: Option<Card> },
+    /// The card doesn't match the suit of the area.
+    WrongSuit { card: Card, suit: Suit },
+    /// The held cards aren't a run: `card` can't go on `on`, the held card under it.
+    OutOfSequence { card: Card, on: Card },
+    /// Nothing can go on a face-down card.
+    FaceDown { top: Card },
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::DoesNotFollow {
+                card,
+                top: Some(top),
+            } => write!(
+                fmt,
+                "Card does not follow: card: {:?}, top: {:?}",
+                card, top
+            ),
+            Rejection::DoesNotFollow { card, top: None } => {
+                write!(fmt, "Card does not follow: card: {:?}, top: empty", card)
+            }
+            Rejection::WrongSuit { card, suit } => {
+                write!(fmt, "Wrong suit: card: {:?}, suit: {:?}", card, suit)
+            }
+            Rejection::OutOfSequence { card, on } => write!(
+                fmt,
+                "Held cards out of sequence: card: {:?}, on: {:?}",
+                card, on
+            ),
+            Rejection::FaceDown { top } => write!(fmt, "Top card is face down: top: {:?}", top),
+        }
+    }
+}
+
+impl Error {
+    /// Explain to the player why the area with the given id turned down their cards, or `None` if
+    /// the error isn't worth bothering them with.
+    pub fn rejection_message(&self, area_id: AreaId) -> Option<String> {
+        match self {
+            Error::InvalidCard { rejection } => Some(match rejection {
+                Rejection::DoesNotFollow {
+                    card,
+                    top: Some(top),
+                } => format!("{} can't go on {}", card, top),
+                Rejection::DoesNotFollow { card, top: None } => match area_id {
+                    AreaId::Foundation(_) => format!("{} can't start a foundation", card),
+                    _ => format!("{} can't go on an empty tableaux", card),
+                },
+                Rejection::WrongSuit { suit, .. } => format!("Foundation only accepts {}", suit),
+                Rejection::OutOfSequence { card, on } => {
+                    format!("Held cards aren't in sequence: {} on {}", card, on)
+                }
+                Rejection::FaceDown { .. } => "Cards can't go on a face-down card".to_string(),
+            }),
+            Error::TooManyCards { .. } => Some(format!(
+                "Only one card at a time can go on {}",
+                area_name(area_id)
+            )),
+            Error::NotSupported { .. } => Some(format!("Cards can't go on {}", area_name(area_id))),
+            Error::NoPassesRemain { .. } => Some("No passes through the stock remain".to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// How to refer to an area when talking to the player.
+fn area_name(area_id: AreaId) -> &'static str {
+    match area_id {
+        AreaId::Stock => "the stock",
+        AreaId::Talon => "the talon",
+        AreaId::Foundation(_) => "a foundation",
+        AreaId::Tableaux(_) => "a tableaux",
+    }
+}
+
 #[derive(Debug)]
 pub enum MoveResult<T, U, E = Error> {
     Moved(T),
@@ -69,11 +159,13 @@ where
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Deserialize, Serialize)]
 pub enum AreaId {
     Stock,
     Talon,
-    Foundation(Suit),
+    /// Foundations are numbered in suit order, one deck after another, so a game with two decks
+    /// has two foundations for each suit.
+    Foundation(u8),
     Tableaux(u8),
 }
 
@@ -83,10 +175,53 @@ pub struct Held {
     pub cards: Vec<Card>,
 }
 
-#[derive(Debug)]
+/// The state of an unselected area, detached from the area itself so that it can be saved and the
+/// area recreated later.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct AreaSnapshot {
+    pub id: AreaId,
+    pub cards: Vec<Card>,
+    pub spread_len: usize,
+}
+
+impl AreaSnapshot {
+    pub fn of(area: &dyn Area) -> AreaSnapshot {
+        AreaSnapshot {
+            id: area.id(),
+            cards: area.as_stack().cards.to_vec(),
+            spread_len: area.spread_len(),
+        }
+    }
+}
+
+/// Recreate an unselected area from its snapshot. The kind of area is decided by the area id.
+pub fn create_area(snapshot: AreaSnapshot, settings: &GameSettings) -> Box<dyn UnselectedArea> {
+    let AreaSnapshot {
+        id,
+        cards,
+        spread_len,
+    } = snapshot;
+
+    match id {
+        AreaId::Stock => stock::UnselectedStock::create(cards, settings),
+        AreaId::Talon => talon::UnselectedTalon::create(cards, spread_len),
+        AreaId::Foundation(index) => {
+            let suit = foundation::suit_for_index(index);
+            foundation::UnselectedFoundation::create(index, suit, cards, settings)
+        }
+        AreaId::Tableaux(index) => {
+            tableaux::UnselectedTableaux::create(index, spread_len, cards, settings)
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
 pub enum Action {
     Draw(usize),
     Restock,
+    /// The area turned its top card face up. There's nothing left to do, but the move still has to
+    /// be recorded.
+    Reveal,
 }
 
 pub trait Area {
@@ -101,6 +236,24 @@ pub trait Area {
 
     fn peek_top_card(&self) -> Option<&Card>;
 
+    /// Check whether this area would take the held cards, without actually moving them.
+    fn accepts_cards(&self, held: &Held) -> Result;
+    /// The most cards that can be picked up from the top of this area at once.
+    fn movable_len(&self) -> usize;
+
+    /// The number of cards spread out at the top of this area, i.e., the revealed cards of a
+    /// tableaux or the fanned cards of the talon. Areas that keep their cards squared up report 0.
+    fn spread_len(&self) -> usize;
+    /// Overwrite the spread length, e.g., to restore it when undoing a move.
+    fn set_spread_len(&mut self, len: usize);
+
+    /// How many more times the player may go through this area after the current pass, if it's
+    /// limited. Only the stock is ever restocked; other areas report `None`.
+    fn passes_remaining(&self) -> Option<usize>;
+    /// Overwrite how many times this area has been restocked, e.g., after restocking or undoing a
+    /// restock. Areas that are never restocked ignore this.
+    fn set_restock_count(&mut self, count: usize);
+
     fn as_stack(&self) -> Stack<'_>;
 
     fn as_area(&self) -> &dyn Area;
@@ -1,6 +1,7 @@
 use crate::{
     model::{
         card::Card,
+        rules::{self, Rules, StockState},
         settings::GameSettings,
         stack::{Orientation, Stack, StackDetails, StackSelection},
     },
@@ -19,6 +20,13 @@ pub struct Selection;
 pub struct Stock<S> {
     cards: Vec<Card>,
     draw_from_stock_len: usize,
+    /// How many times the player may go through the stock, if it's limited
+    max_passes: Option<usize>,
+    /// How many times the stock has been refilled from the talon so far
+    restock_count: usize,
+    /// Whether the stock is face up and spread out, so that every card in it can be seen
+    open: bool,
+    rules: &'static dyn Rules,
     selection: S,
 }
 
@@ -30,6 +38,18 @@ impl<S> Stock<S> {
         AreaId::Stock
     }
 
+    /// Cards are only ever drawn from the stock, never picked up.
+    fn movable_len(&self) -> usize {
+        0
+    }
+
+    /// Each restock starts another pass through the stock, and the first pass is the one we start
+    /// with.
+    fn passes_remaining(&self) -> Option<usize> {
+        self.max_passes
+            .map(|max_passes| max_passes.saturating_sub(self.restock_count + 1))
+    }
+
     fn validate_cards(&self, held: &Held) -> Result {
         if held.source == self.id() || held.source == AreaId::Talon {
             // We'll always take back our own cards, and we'll allow cards from the talon to be
@@ -64,14 +84,24 @@ impl<S> Stock<S> {
     }
 
     fn as_stack(&self, mode: Option<Selection>) -> Stack<'_> {
+        let cards_len = self.cards.len();
+
+        // An open stock shows all its cards. Otherwise, only the top card is shown, with a hint of
+        // another beneath it.
+        let (face_up_len, visible_len, spread_len) = if self.open {
+            (cards_len, cards_len, cards_len)
+        } else {
+            (0, 2, 1)
+        };
+
         Stack {
             cards: &self.cards,
             details: StackDetails {
                 orientation: Orientation::Horizontal,
-                len: self.cards.len(),
-                face_up_len: 0,
-                visible_len: 2,
-                spread_len: 1,
+                len: cards_len,
+                face_up_len,
+                visible_len,
+                spread_len,
                 selection: mode.map(|_| StackSelection {
                     len: 1,
                     held: false,
@@ -84,6 +114,10 @@ impl<S> Stock<S> {
         Stock {
             cards: self.cards,
             draw_from_stock_len: self.draw_from_stock_len,
+            max_passes: self.max_passes,
+            restock_count: self.restock_count,
+            open: self.open,
+            rules: self.rules,
             selection,
         }
     }
@@ -94,6 +128,10 @@ impl UnselectedStock {
         Box::new(Stock {
             cards,
             draw_from_stock_len: settings.draw_from_stock_len,
+            max_passes: settings.stock_pass_limit(),
+            restock_count: 0,
+            open: settings.open_deck,
+            rules: rules::rules_for(settings.variant),
             selection: (),
         })
     }
@@ -128,6 +166,28 @@ impl Area for UnselectedStock {
         self.cards.last()
     }
 
+    fn accepts_cards(&self, held: &Held) -> Result {
+        Stock::validate_cards(self, held)
+    }
+
+    fn movable_len(&self) -> usize {
+        Stock::movable_len(self)
+    }
+
+    fn spread_len(&self) -> usize {
+        0
+    }
+
+    fn set_spread_len(&mut self, _len: usize) {}
+
+    fn passes_remaining(&self) -> Option<usize> {
+        Stock::passes_remaining(self)
+    }
+
+    fn set_restock_count(&mut self, count: usize) {
+        self.restock_count = count;
+    }
+
     fn as_stack(&self) -> Stack<'_> {
         self.as_stack(None)
     }
@@ -170,6 +230,28 @@ impl Area for SelectedStock {
         self.cards.last()
     }
 
+    fn accepts_cards(&self, held: &Held) -> Result {
+        Stock::validate_cards(self, held)
+    }
+
+    fn movable_len(&self) -> usize {
+        Stock::movable_len(self)
+    }
+
+    fn spread_len(&self) -> usize {
+        0
+    }
+
+    fn set_spread_len(&mut self, _len: usize) {}
+
+    fn passes_remaining(&self) -> Option<usize> {
+        Stock::passes_remaining(self)
+    }
+
+    fn set_restock_count(&mut self, count: usize) {
+        self.restock_count = count;
+    }
+
     fn as_stack(&self) -> Stack<'_> {
         self.as_stack(Some(self.selection))
     }
@@ -206,11 +288,13 @@ impl SelectedArea for SelectedStock {
     }
 
     fn activate(&mut self) -> Result<Option<Action>> {
-        if self.cards.is_empty() {
-            Ok(Some(Action::Restock))
-        } else {
-            Ok(Some(Action::Draw(self.draw_from_stock_len)))
-        }
+        let action = self.rules.stock_action(&StockState {
+            len: self.cards.len(),
+            draw_len: self.draw_from_stock_len,
+            max_passes: self.max_passes,
+            passes_remaining: self.passes_remaining(),
+        })?;
+        Ok(Some(action))
     }
 
     fn pick_up(&mut self) -> Result {
@@ -1,15 +1,17 @@
 use crate::{
     model::{
         area::{AlreadyHeld, MaxSelection, MinSelection, NothingHeld},
-        card::{Card, Rank},
+        card::Card,
+        rules::{self, Rules},
+        settings::GameSettings,
         stack::{Orientation, Stack, StackDetails, StackSelection},
     },
     utils::vec::SplitOffBounded,
 };
 
 use super::{
-    Action, Area, AreaId, Held, InvalidCard, MoveResult, NothingToSelect, Result, SelectedArea,
-    SnafuSelectorExt, UnselectedArea,
+    Action, Area, AreaId, Held, InvalidCard, MoveResult, NothingToSelect, Rejection, Result,
+    SelectedArea, SnafuSelectorExt, UnselectedArea,
 };
 
 #[derive(Copy, Clone, Debug)]
@@ -23,6 +25,7 @@ pub struct Tableaux<S> {
     index: u8,
     cards: Vec<Card>,
     revealed_len: usize,
+    rules: &'static dyn Rules,
     selection: S,
 }
 
@@ -34,36 +37,29 @@ impl<S> Tableaux<S> {
         AreaId::Tableaux(self.index)
     }
 
+    /// Only the revealed cards can be picked up.
+    fn movable_len(&self) -> usize {
+        self.revealed_len
+    }
+
     fn accepts_cards(&self, held: &Held) -> Result {
         if held.source == self.id() {
             // We'll always take back our own cards.
             Ok(())
-        } else if let Some(card) = held.cards.first() {
-            if let Some(tableaux_card) = self.cards.last() {
-                // TODO: Check that the pile itself is legit.
-                ensure!(
-                    self.revealed_len > 0
-                        && card.rank.is_followed_by(tableaux_card.rank)
-                        && card.color() != tableaux_card.color(),
-                    InvalidCard {
-                        message: format!(
-                            "Card does not follow: card: {:?}, top: {:?}",
-                            card, tableaux_card
-                        )
-                    }
-                );
-                Ok(())
-            } else {
+        } else if held.cards.is_empty() {
+            Ok(())
+        } else {
+            // Nothing can go on a face-down card, whatever the rules.
+            if let Some(top) = self.cards.last() {
                 ensure!(
-                    card.rank == Rank::King,
+                    self.revealed_len > 0,
                     InvalidCard {
-                        message: format!("Card does not follow: card: {:?}, top: empty", card)
+                        rejection: Rejection::FaceDown { top: top.clone() }
                     }
                 );
-                Ok(())
             }
-        } else {
-            Ok(())
+
+            self.rules.tableaux_accepts(self.cards.last(), &held.cards)
         }
     }
 
@@ -107,17 +103,24 @@ impl<S> Tableaux<S> {
             index: self.index,
             cards: self.cards,
             revealed_len: self.revealed_len,
+            rules: self.rules,
             selection,
         }
     }
 }
 
 impl UnselectedTableaux {
-    pub fn create(index: u8, revealed_len: usize, cards: Vec<Card>) -> Box<dyn UnselectedArea> {
+    pub fn create(
+        index: u8,
+        revealed_len: usize,
+        cards: Vec<Card>,
+        settings: &GameSettings,
+    ) -> Box<dyn UnselectedArea> {
         Box::new(Tableaux {
             index,
             cards,
             revealed_len,
+            rules: rules::rules_for(settings.variant),
             selection: (),
         })
     }
@@ -152,6 +155,28 @@ impl Area for UnselectedTableaux {
         self.cards.last()
     }
 
+    fn accepts_cards(&self, held: &Held) -> Result {
+        Tableaux::accepts_cards(self, held)
+    }
+
+    fn movable_len(&self) -> usize {
+        Tableaux::movable_len(self)
+    }
+
+    fn spread_len(&self) -> usize {
+        self.revealed_len
+    }
+
+    fn set_spread_len(&mut self, len: usize) {
+        self.revealed_len = len;
+    }
+
+    fn passes_remaining(&self) -> Option<usize> {
+        None
+    }
+
+    fn set_restock_count(&mut self, _count: usize) {}
+
     fn as_stack(&self) -> Stack<'_> {
         self.as_stack(None)
     }
@@ -203,6 +228,31 @@ impl Area for SelectedTableaux {
         self.cards.last()
     }
 
+    fn accepts_cards(&self, held: &Held) -> Result {
+        Tableaux::accepts_cards(self, held)
+    }
+
+    fn movable_len(&self) -> usize {
+        Tableaux::movable_len(self)
+    }
+
+    fn spread_len(&self) -> usize {
+        self.revealed_len
+    }
+
+    fn set_spread_len(&mut self, len: usize) {
+        self.revealed_len = len;
+
+        // Hiding cards again, e.g., by undoing a reveal, mustn't leave face-down cards selected.
+        self.selection.len = self.selection.len.min(len).max(1);
+    }
+
+    fn passes_remaining(&self) -> Option<usize> {
+        None
+    }
+
+    fn set_restock_count(&mut self, _count: usize) {}
+
     fn as_stack(&self) -> Stack<'_> {
         self.as_stack(Some(self.selection))
     }
@@ -270,7 +320,7 @@ impl SelectedArea for SelectedTableaux {
             Ok(None)
         } else if !self.cards.is_empty() {
             self.revealed_len += 1;
-            Ok(None)
+            Ok(Some(Action::Reveal))
         } else {
             NothingToSelect {
                 message: "Empty area",
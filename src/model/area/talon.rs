@@ -31,6 +31,10 @@ impl<S> Talon<S> {
         AreaId::Talon
     }
 
+    fn movable_len(&self) -> usize {
+        self.cards.len().min(1)
+    }
+
     fn validate_cards(&self, held: &Held) -> Result {
         if held.source == self.id() || held.source == AreaId::Stock {
             // We'll always take back our own cards, and we'll allow cards from the stock to be
@@ -135,6 +139,28 @@ impl<'a> Area for UnselectedTalon {
         self.cards.last()
     }
 
+    fn accepts_cards(&self, held: &Held) -> Result {
+        Talon::validate_cards(self, held)
+    }
+
+    fn movable_len(&self) -> usize {
+        Talon::movable_len(self)
+    }
+
+    fn spread_len(&self) -> usize {
+        self.fanned_len
+    }
+
+    fn set_spread_len(&mut self, len: usize) {
+        self.fanned_len = len;
+    }
+
+    fn passes_remaining(&self) -> Option<usize> {
+        None
+    }
+
+    fn set_restock_count(&mut self, _count: usize) {}
+
     fn as_stack(&self) -> Stack<'_> {
         self.as_stack(None)
     }
@@ -180,6 +206,28 @@ impl Area for SelectedTalon {
         self.cards.last()
     }
 
+    fn accepts_cards(&self, held: &Held) -> Result {
+        Talon::validate_cards(self, held)
+    }
+
+    fn movable_len(&self) -> usize {
+        Talon::movable_len(self)
+    }
+
+    fn spread_len(&self) -> usize {
+        self.fanned_len
+    }
+
+    fn set_spread_len(&mut self, len: usize) {
+        self.fanned_len = len;
+    }
+
+    fn passes_remaining(&self) -> Option<usize> {
+        None
+    }
+
+    fn set_restock_count(&mut self, _count: usize) {}
+
     fn as_stack(&self) -> Stack<'_> {
         self.as_stack(Some(self.selection))
     }
@@ -1,12 +1,17 @@
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 use snafu::{OptionExt, ResultExt};
 use std::{collections::HashMap, fmt};
 
-use crate::utils::vec::SplitOffAround;
+use crate::utils::vec::{SplitOffAround, SplitOffBounded};
 
-use super::area::{
-    self, move_selection, Action, Area, AreaId, MoveResult, SelectedArea, SelectionMove,
-    UnselectedArea,
+use super::{
+    area::{
+        self, create_area, foundation::suit_for_index, move_selection, Action, Area, AreaId,
+        AreaSnapshot, Held, MoveResult, SelectedArea, SelectionMove, UnselectedArea,
+    },
+    history::{History, Move},
+    settings::GameSettings,
 };
 
 #[derive(Debug, Snafu)]
@@ -28,10 +33,55 @@ pub enum Error {
         area_id: AreaId,
         source: area::Error,
     },
+
+    #[snafu(display("Nothing to undo"))]
+    NothingToUndo,
+
+    #[snafu(display("Nothing to redo"))]
+    NothingToRedo,
 }
 
 pub type Result<T> = ::std::result::Result<T, Error>;
 
+/// The state of an [area list](AreaList), detached from the areas themselves so that it can be
+/// saved and the list recreated later.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AreaListSnapshot {
+    /// The areas in order. Any held cards are in the selected area.
+    pub areas: Vec<AreaSnapshot>,
+    pub selected: AreaId,
+    /// How many cards at the top of the selected area are selected.
+    pub selection_len: usize,
+    /// Where the held cards came from, if any cards are held.
+    pub held_from: Option<AreaId>,
+    pub pick_up_spread_len: Option<usize>,
+    pub history: History,
+}
+
+impl AreaListSnapshot {
+    /// Put any held cards back into the area they were picked up from, and put them down, as if
+    /// the player had returned them.
+    pub fn return_held(&mut self) -> Result<()> {
+        if let Some(source) = self.held_from.filter(|&source| source != self.selected) {
+            let target_snapshot = find_snapshot(&mut self.areas, self.selected)?;
+            let held_cards = target_snapshot.cards.split_off_bounded(self.selection_len);
+            target_snapshot.spread_len =
+                target_snapshot.spread_len.saturating_sub(held_cards.len());
+
+            let source_snapshot = find_snapshot(&mut self.areas, source)?;
+            source_snapshot.spread_len += held_cards.len();
+            source_snapshot.cards.extend(held_cards);
+
+            self.selected = source;
+        }
+
+        self.held_from = None;
+        self.pick_up_spread_len = None;
+
+        Ok(())
+    }
+}
+
 /// A list of [areas](Area) with one [selected](SelectedArea) and the rest
 /// [unselected](UnselectedArea) that can efficiently move the selection and map [area ids](AreaId)
 /// to areas.
@@ -50,6 +100,11 @@ pub struct AreaList {
     /// The list of areas after the selected area. This list is kept in reverse order so we can
     /// efficiently push and pop to move left and right.
     after_areas: Vec<Box<dyn UnselectedArea>>,
+    /// The moves made so far, for undo and redo.
+    history: History,
+    /// The spread length of the area the held cards were picked up from, as it was before they
+    /// were picked up. Recorded with the move once the cards are put down somewhere else.
+    pick_up_spread_len: Option<usize>,
 }
 
 // This list is always non-empty
@@ -102,6 +157,8 @@ impl AreaList {
                 before_areas: areas,
                 selected_area: None,
                 after_areas: vec![],
+                history: History::new(),
+                pick_up_spread_len: None,
             })
         } else {
             // Reverse the list of areas, because we keep after_areas in reverse order for efficient
@@ -122,10 +179,78 @@ impl AreaList {
                 before_areas: vec![],
                 selected_area: Some(selected_area),
                 after_areas: areas,
+                history: History::new(),
+                pick_up_spread_len: None,
             })
         }
     }
 
+    /// Recreate an area list from a snapshot. The areas are all created unselected, and then we
+    /// select (and possibly pick up and move) cards the same way a player would, so that every
+    /// area goes through its usual selection rules.
+    pub fn from_snapshot(
+        mut snapshot: AreaListSnapshot,
+        settings: &GameSettings,
+    ) -> Result<AreaList> {
+        let selected = snapshot.selected;
+        let selection_len = snapshot.selection_len;
+        let held_from = snapshot.held_from;
+        let pick_up_spread_len = snapshot.pick_up_spread_len;
+
+        // Held cards that came from another area go back where they came from, so that we can
+        // pick them up and carry them over again.
+        snapshot.return_held()?;
+
+        let AreaListSnapshot { areas, history, .. } = snapshot;
+
+        let areas = areas
+            .into_iter()
+            .map(|snapshot| create_area(snapshot, settings))
+            .collect::<Vec<_>>();
+        let mut area_list = AreaList::new(areas)?;
+
+        area_list.move_selection(held_from.unwrap_or(selected))?;
+
+        for _ in 1..selection_len {
+            area_list.select_more()?;
+        }
+
+        if held_from.is_some() {
+            let selected_area = area_list.selected_mut();
+            let selected_area_id = selected_area.id();
+
+            selected_area.pick_up().context(SelectionError {
+                area_id: selected_area_id,
+            })?;
+
+            area_list.move_selection(selected)?;
+        }
+
+        area_list.pick_up_spread_len = pick_up_spread_len;
+        area_list.history = history;
+        area_list.update_restock_count()?;
+
+        Ok(area_list)
+    }
+
+    pub fn snapshot(&self) -> AreaListSnapshot {
+        let selected_area = self.selected();
+
+        AreaListSnapshot {
+            areas: self.iter().map(AreaSnapshot::of).collect(),
+            selected: selected_area.id(),
+            selection_len: selected_area
+                .as_stack()
+                .details
+                .selection
+                .map(|selection| selection.len)
+                .unwrap_or(1),
+            held_from: selected_area.held_from(),
+            pick_up_spread_len: self.pick_up_spread_len,
+            history: self.history.clone(),
+        }
+    }
+
     pub fn len(&self) -> usize {
         self.before_areas.len() + self.after_areas.len() + 1
     }
@@ -204,6 +329,10 @@ impl AreaList {
         self.area_ids.keys().cloned().collect::<Vec<_>>()
     }
 
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
     #[allow(clippy::redundant_closure)]
     pub fn iter<'b>(&'b self) -> impl Iterator<Item = &'b dyn Area> + 'b {
         let before_iter = self.before_areas.iter().map(|area| area.as_area());
@@ -310,54 +439,387 @@ impl AreaList {
 
     pub fn activate_selected(&mut self) -> Result<Vec<AreaId>> {
         let selected_area = self.selected_mut();
+        let selected_area_id = selected_area.id();
+        let held_from = selected_area.held_from();
+        let spread_len = selected_area.spread_len();
+
         let action = selected_area.activate().context(UnableToActivate {
-            area_id: selected_area.id(),
+            area_id: selected_area_id,
         })?;
 
         match action {
             Some(Action::Draw(len)) => {
-                // Take the next `len` cards from the stock. We reverse the held cards because they're
-                // being drawn one-by-one into the talon, so the first drawn is at the bottom of the
-                // pile.
-                let mut held = self.get_by_area_id_mut(AreaId::Stock)?.take_cards(len);
-                held.cards.reverse();
-
-                // The talon should always accept cards from the stock, so no need to handle putting
-                // them back on failure; just blow up.
-                self.get_by_area_id_mut(AreaId::Talon)?
-                    .give_cards(held)
-                    .into_result()
-                    .context(SelectionError {
-                        area_id: AreaId::Talon,
-                    })?;
+                let talon_spread_len = self.get_by_area_id(AreaId::Talon)?.spread_len();
+                let len = self.draw(len)?;
+
+                self.history.record(Move::Draw {
+                    len,
+                    talon_spread_len,
+                });
 
                 Ok(vec![AreaId::Stock, AreaId::Talon])
             }
             Some(Action::Restock) => {
-                // Flip the talon onto the stock.
-                let mut held = self.get_by_area_id_mut(AreaId::Talon)?.take_all_cards();
-                held.cards.reverse();
-
-                // The stock should always accept cards from the talon, so no need to handle putting
-                // them back on failure; just blow up.
-                self.get_by_area_id_mut(AreaId::Stock)?
-                    .give_cards(held)
-                    .into_result()
-                    .context(SelectionError {
-                        area_id: AreaId::Stock,
-                    })?;
+                let talon_spread_len = self.get_by_area_id(AreaId::Talon)?.spread_len();
+                let len = self.restock()?;
+
+                self.history.record(Move::Restock {
+                    len,
+                    talon_spread_len,
+                });
+                self.update_restock_count()?;
 
                 Ok(vec![AreaId::Stock, AreaId::Talon])
             }
-            None => Ok(vec![selected_area.id()]),
+            Some(Action::Reveal) => {
+                self.history.record(Move::Reveal {
+                    area_id: selected_area_id,
+                });
+
+                Ok(vec![selected_area_id])
+            }
+            None => {
+                let selected_area = self.selected();
+
+                match (held_from, selected_area.held_from()) {
+                    // We just picked up some cards, so remember how the area looked beforehand.
+                    (None, Some(_)) => {
+                        self.pick_up_spread_len = Some(spread_len);
+                    }
+                    // We just put down cards that came from another area.
+                    (Some(source), None) if source != selected_area_id => {
+                        let cards = selected_area.as_stack().cards;
+                        let held_len = selected_area
+                            .as_stack()
+                            .details
+                            .selection
+                            .map(|selection| selection.len)
+                            .unwrap_or_default();
+                        let cards = cards[cards.len().saturating_sub(held_len)..].to_vec();
+
+                        let source_spread_len = self.pick_up_spread_len.take().unwrap_or_default();
+
+                        self.history.record(Move::Transfer {
+                            source,
+                            target: selected_area_id,
+                            cards,
+                            source_spread_len,
+                        });
+                    }
+                    // We put the cards back down where we found them.
+                    (Some(_), None) => {
+                        self.pick_up_spread_len = None;
+                    }
+                    _ => {}
+                }
+
+                Ok(vec![selected_area_id])
+            }
         }
     }
 
+    /// Draw up to `len` cards from the stock onto the talon, returning how many were drawn.
+    fn draw(&mut self, len: usize) -> Result<usize> {
+        // Take the next `len` cards from the stock. We reverse the held cards because they're
+        // being drawn one-by-one into the talon, so the first drawn is at the bottom of the
+        // pile.
+        let mut held = self.get_by_area_id_mut(AreaId::Stock)?.take_cards(len);
+        held.cards.reverse();
+        let len = held.cards.len();
+
+        // The talon should always accept cards from the stock, so no need to handle putting
+        // them back on failure; just blow up.
+        self.get_by_area_id_mut(AreaId::Talon)?
+            .give_cards(held)
+            .into_result()
+            .context(SelectionError {
+                area_id: AreaId::Talon,
+            })?;
+
+        Ok(len)
+    }
+
+    /// Flip the talon back over onto the stock, returning how many cards were moved.
+    fn restock(&mut self) -> Result<usize> {
+        let mut held = self.get_by_area_id_mut(AreaId::Talon)?.take_all_cards();
+        held.cards.reverse();
+        let len = held.cards.len();
+
+        // The stock should always accept cards from the talon, so no need to handle putting
+        // them back on failure; just blow up.
+        self.get_by_area_id_mut(AreaId::Stock)?
+            .give_cards(held)
+            .into_result()
+            .context(SelectionError {
+                area_id: AreaId::Stock,
+            })?;
+
+        Ok(len)
+    }
+
+    /// Let the stock know how many times it has been restocked, counting the restocks that haven't
+    /// been undone, so that it can enforce any limit on passes through it.
+    fn update_restock_count(&mut self) -> Result<()> {
+        let restock_count = self.restock_count();
+
+        self.get_by_area_id_mut(AreaId::Stock)?
+            .set_restock_count(restock_count);
+
+        Ok(())
+    }
+
+    /// How many times the stock has been refilled from the talon, not counting restocks that have
+    /// been undone.
+    pub fn restock_count(&self) -> usize {
+        self.history
+            .moves()
+            .iter()
+            .filter(|game_move| matches!(game_move, Move::Restock { .. }))
+            .count()
+    }
+
+    /// Move every card that can safely go to its foundation, recording each move so it can be
+    /// undone. Nothing is moved while cards are held.
+    pub fn auto_move_to_foundation(&mut self) -> Result<Vec<AreaId>> {
+        let mut area_ids = vec![];
+
+        if self.selected().is_held() {
+            return Ok(area_ids);
+        }
+
+        while let Some((source, target)) = self.find_foundation_move(true) {
+            area_ids.extend(self.move_to_foundation(source, target)?);
+        }
+
+        Ok(area_ids.into_iter().unique().collect())
+    }
+
+    /// Move a single card that fits on its foundation, whether or not it might still be needed in
+    /// the tableaux. Returns no area ids if no card fits, or if cards are held.
+    pub fn step_to_foundation(&mut self) -> Result<Vec<AreaId>> {
+        if self.selected().is_held() {
+            return Ok(vec![]);
+        }
+
+        if let Some((source, target)) = self.find_foundation_move(false) {
+            self.move_to_foundation(source, target)
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    /// Find a face-up card on the talon or a tableaux that can go to a foundation. If `safe_only`
+    /// is set, the card also has to be safe to move, i.e., without any chance of being needed in
+    /// the tableaux later, because every foundation of the opposite color already has the cards
+    /// that could be built on it.
+    fn find_foundation_move(&self, safe_only: bool) -> Option<(AreaId, AreaId)> {
+        let foundations = self
+            .iter()
+            .filter(|area| matches!(area.id(), AreaId::Foundation(_)))
+            .collect::<Vec<_>>();
+
+        self.iter()
+            .filter(|area| match area.id() {
+                AreaId::Talon | AreaId::Tableaux(_) => area.movable_len() > 0,
+                _ => false,
+            })
+            .find_map(|area| {
+                let card = area.peek_top_card()?;
+
+                let held = Held {
+                    source: area.id(),
+                    cards: vec![card.clone()],
+                };
+                let target = foundations
+                    .iter()
+                    .find(|foundation| foundation.accepts_cards(&held).is_ok())?;
+
+                let rank = u8::from(card.rank);
+                let is_safe = rank <= 2
+                    || foundations.iter().all(|foundation| match foundation.id() {
+                        AreaId::Foundation(index)
+                            if suit_for_index(index).color() != card.color() =>
+                        {
+                            let foundation_rank = foundation
+                                .peek_top_card()
+                                .map_or(0, |card| u8::from(card.rank));
+                            foundation_rank + 1 >= rank
+                        }
+                        _ => true,
+                    });
+
+                if is_safe || !safe_only {
+                    Some((area.id(), target.id()))
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Move the top card of the source area onto a foundation and record the move. The caller is
+    /// responsible for checking that the foundation accepts it.
+    fn move_to_foundation(&mut self, source: AreaId, target: AreaId) -> Result<Vec<AreaId>> {
+        let mut area_ids = vec![source, target];
+
+        let source_area = self.get_by_area_id(source)?;
+        let source_spread_len = source_area.spread_len();
+        let cards = source_area.peek_top_card().cloned().into_iter().collect();
+
+        self.shift_cards(source, target, 1, false)?;
+
+        self.history.record(Move::Transfer {
+            source,
+            target,
+            cards,
+            source_spread_len,
+        });
+
+        // An empty area can't stay selected, so follow the card.
+        if self.selected().id() == source && self.selected().peek_top_card().is_none() {
+            area_ids.extend(self.move_selection(target)?);
+        }
+
+        Ok(area_ids)
+    }
+
+    pub fn undo(&mut self) -> Result<Vec<AreaId>> {
+        let mut area_ids = self.put_back_held()?;
+        let undone_move = self.history.undo().context(NothingToUndo)?;
+
+        let new_selection = match undone_move {
+            Move::Transfer {
+                source,
+                target,
+                cards,
+                source_spread_len,
+            } => {
+                self.shift_cards(target, source, cards.len(), false)?;
+                self.get_by_area_id_mut(source)?
+                    .set_spread_len(source_spread_len);
+                area_ids.extend(&[source, target]);
+                source
+            }
+            Move::Draw {
+                len,
+                talon_spread_len,
+            } => {
+                self.shift_cards(AreaId::Talon, AreaId::Stock, len, true)?;
+                self.get_by_area_id_mut(AreaId::Talon)?
+                    .set_spread_len(talon_spread_len);
+                area_ids.extend(&[AreaId::Stock, AreaId::Talon]);
+                AreaId::Stock
+            }
+            Move::Restock {
+                len,
+                talon_spread_len,
+            } => {
+                self.shift_cards(AreaId::Stock, AreaId::Talon, len, true)?;
+                self.get_by_area_id_mut(AreaId::Talon)?
+                    .set_spread_len(talon_spread_len);
+                self.update_restock_count()?;
+                area_ids.extend(&[AreaId::Stock, AreaId::Talon]);
+                AreaId::Stock
+            }
+            Move::Reveal { area_id } => {
+                let area = self.get_by_area_id_mut(area_id)?;
+                area.set_spread_len(area.spread_len().saturating_sub(1));
+                area_ids.push(area_id);
+                area_id
+            }
+        };
+
+        area_ids.extend(self.move_selection(new_selection)?);
+        Ok(area_ids.into_iter().unique().collect())
+    }
+
+    pub fn redo(&mut self) -> Result<Vec<AreaId>> {
+        let mut area_ids = self.put_back_held()?;
+        let redone_move = self.history.redo().context(NothingToRedo)?;
+
+        let new_selection = match redone_move {
+            Move::Transfer {
+                source,
+                target,
+                cards,
+                ..
+            } => {
+                self.shift_cards(source, target, cards.len(), false)?;
+                area_ids.extend(&[source, target]);
+                target
+            }
+            Move::Draw { len, .. } => {
+                self.shift_cards(AreaId::Stock, AreaId::Talon, len, true)?;
+                self.get_by_area_id_mut(AreaId::Talon)?.set_spread_len(len);
+                area_ids.extend(&[AreaId::Stock, AreaId::Talon]);
+                AreaId::Stock
+            }
+            Move::Restock { len, .. } => {
+                self.shift_cards(AreaId::Talon, AreaId::Stock, len, true)?;
+                self.update_restock_count()?;
+                area_ids.extend(&[AreaId::Stock, AreaId::Talon]);
+                AreaId::Stock
+            }
+            Move::Reveal { area_id } => {
+                let area = self.get_by_area_id_mut(area_id)?;
+                area.set_spread_len(area.spread_len() + 1);
+                area_ids.push(area_id);
+                area_id
+            }
+        };
+
+        area_ids.extend(self.move_selection(new_selection)?);
+        Ok(area_ids.into_iter().unique().collect())
+    }
+
+    /// Return any held cards and put them down, so that the areas are settled before replaying
+    /// history.
+    fn put_back_held(&mut self) -> Result<Vec<AreaId>> {
+        let area_ids = self.return_held()?;
+
+        let selected_area = self.selected_mut();
+        let selected_area_id = selected_area.id();
+
+        if selected_area.is_held() {
+            selected_area.put_down().context(SelectionError {
+                area_id: selected_area_id,
+            })?;
+        }
+
+        self.pick_up_spread_len = None;
+
+        Ok(area_ids)
+    }
+
+    /// Move the top `len` cards from one area to another without checking whether the target
+    /// accepts them, e.g., to replay history. Spread lengths are left for the caller to fix up.
+    fn shift_cards(
+        &mut self,
+        source: AreaId,
+        target: AreaId,
+        len: usize,
+        reverse: bool,
+    ) -> Result<()> {
+        let mut held = self.get_by_area_id_mut(source)?.take_cards(len);
+
+        if reverse {
+            held.cards.reverse();
+        }
+
+        // Every area will take back its own cards, so we pretend the cards came from the target.
+        held.source = target;
+
+        self.get_by_area_id_mut(target)?
+            .give_cards(held)
+            .into_result()
+            .context(SelectionError { area_id: target })
+    }
+
     pub fn return_held(&mut self) -> Result<Vec<AreaId>> {
         if let Some(original_area_id) = self.selected().held_from() {
             let affected_area_ids = self.move_selection(original_area_id)?;
 
             if !affected_area_ids.is_empty() {
+                self.pick_up_spread_len = None;
                 self.selected_mut().put_down().context(SelectionError {
                     area_id: original_area_id,
                 })?;
@@ -399,6 +861,13 @@ impl AreaList {
     }
 }
 
+fn find_snapshot(areas: &mut [AreaSnapshot], area_id: AreaId) -> Result<&mut AreaSnapshot> {
+    areas
+        .iter_mut()
+        .find(|snapshot| snapshot.id == area_id)
+        .context(UnknownAreaId { area_id })
+}
+
 impl fmt::Debug for AreaList {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         // We don't assume that Areas implement Debug, so we'll just format them as their area ids.
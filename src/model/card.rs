@@ -1,12 +1,28 @@
+use serde::{Deserialize, Serialize};
 use std::{convert::TryInto, fmt};
 
+/// The number of suits in a deck.
+pub static SUIT_LEN: u8 = 4;
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Color {
     Black,
     Red,
 }
 
-#[derive(Copy, Clone, Debug, Eq, Ord, PartialEq, PartialOrd, IntoPrimitive, TryFromPrimitive)]
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Eq,
+    Ord,
+    PartialEq,
+    PartialOrd,
+    IntoPrimitive,
+    TryFromPrimitive,
+    Deserialize,
+    Serialize,
+)]
 #[repr(u8)]
 pub enum Rank {
     Ace = 1,
@@ -58,6 +74,8 @@ impl fmt::Display for Rank {
     IntoPrimitive,
     TryFromPrimitive,
     Display,
+    Deserialize,
+    Serialize,
 )]
 #[repr(u8)]
 pub enum Suit {
@@ -80,11 +98,11 @@ impl Suit {
     }
 
     pub fn values() -> impl Iterator<Item = Suit> {
-        (0u8..4).map(|value| value.try_into().unwrap())
+        (0u8..SUIT_LEN).map(|value| value.try_into().unwrap())
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Deserialize, Serialize)]
 pub struct Card {
     pub suit: Suit,
     pub rank: Rank,
@@ -95,3 +113,9 @@ impl Card {
         self.suit.color()
     }
 }
+
+impl fmt::Display for Card {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{}{}", self.rank, self.suit)
+    }
+}
@@ -1,12 +1,16 @@
 use std::fmt;
 
-use num_traits::ToPrimitive;
-use rand::{seq::SliceRandom, thread_rng};
+use rand::{thread_rng, Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
-use super::{area, area_list, settings, Card, Game, Rank, Suit};
+use super::{area, area_list, card::SUIT_LEN, rules, settings, Card, Game, Rank, Suit};
 
 pub trait Dealer {
-    fn deal_game(&self, settings: &settings::GameSettings) -> Game;
+    /// Pick the seed for a new deal, or `None` if this dealer doesn't shuffle.
+    fn new_seed(&self) -> Option<u64>;
+
+    /// Deal a game from the given seed. Dealing the same seed again deals the same game.
+    fn deal_game(&self, seed: Option<u64>, settings: &settings::GameSettings) -> Game;
 }
 
 pub fn create_dealer(mode: settings::DealerMode) -> Box<dyn Dealer> {
@@ -18,6 +22,9 @@ pub fn create_dealer(mode: settings::DealerMode) -> Box<dyn Dealer> {
         settings::DealerMode::Random => Box::new(StandardDealer {
             shuffle: RandomShuffle,
         }),
+        settings::DealerMode::Seeded(seed) => Box::new(StandardDealer {
+            shuffle: SeededShuffle(seed),
+        }),
     }
 }
 
@@ -25,20 +32,24 @@ pub fn create_dealer(mode: settings::DealerMode) -> Box<dyn Dealer> {
 struct AutoWinDealer;
 
 impl Dealer for AutoWinDealer {
-    fn deal_game(&self, settings: &settings::GameSettings) -> Game {
+    fn new_seed(&self) -> Option<u64> {
+        None
+    }
+
+    fn deal_game(&self, _seed: Option<u64>, settings: &settings::GameSettings) -> Game {
         let stock = area::stock::UnselectedStock::create(vec![], settings);
         let talon = area::talon::UnselectedTalon::create(vec![], 0);
 
         let mut tableaux_areas = (0..settings.tableaux_len)
-            .map(|index| area::tableaux::UnselectedTableaux::create(index, 0, vec![]))
+            .map(|index| area::tableaux::UnselectedTableaux::create(index, 0, vec![], settings))
             .collect::<Vec<_>>();
 
-        let mut foundation_areas = Suit::values()
-            .map(|suit| {
+        let mut foundation_areas = foundation_suits(settings)
+            .map(|(index, suit)| {
                 let cards = Rank::values()
                     .map(|rank| Card { suit, rank })
                     .collect::<Vec<_>>();
-                area::foundation::UnselectedFoundation::create(suit, cards, settings)
+                area::foundation::UnselectedFoundation::create(index, suit, cards, settings)
             })
             .collect::<Vec<_>>();
 
@@ -47,7 +58,7 @@ impl Dealer for AutoWinDealer {
         areas.append(&mut tableaux_areas);
 
         let areas = area_list::AreaList::new(areas).expect("Unable to create AreaList");
-        Game::new(areas)
+        Game::new(areas, None, settings)
     }
 }
 
@@ -63,42 +74,26 @@ impl<S> Dealer for StandardDealer<S>
 where
     S: Shuffle + fmt::Debug,
 {
-    fn deal_game(&self, settings: &settings::GameSettings) -> Game {
-        let mut deck = S::create_deck();
-
-        let mut tableaux_areas = {
-            let len: usize = settings.tableaux_len.into();
-
-            let mut piles: Vec<Vec<Card>> = vec![vec![]; len];
-            let indexes = (0..7).flat_map(|level| level..7);
-
-            // Reversed because we want to deal from the "top of the deck", which is actually the
-            // end of the vector. If we did our math right, these two vectors (cards and indexes)
-            // will have the same length.
-            for index in indexes {
-                if let Some(card) = deck.pop() {
-                    piles[index].push(card);
-                } else {
-                    break;
-                }
-            }
-
-            piles
-                .into_iter()
-                .enumerate()
-                .map(|(index, cards)| {
-                    // TODO: Use a proper Result type instead of unwrap.
-                    let index = index.to_u8().unwrap();
-                    area::tableaux::UnselectedTableaux::create(index, 1, cards)
-                })
-                .collect::<Vec<_>>()
-        };
+    fn new_seed(&self) -> Option<u64> {
+        self.shuffle.new_seed()
+    }
+
+    fn deal_game(&self, seed: Option<u64>, settings: &settings::GameSettings) -> Game {
+        let mut deck = self.shuffle.create_deck(seed, settings.deck_count);
+
+        let mut tableaux_areas = rules::rules_for(settings.variant)
+            .deal_tableaux(&mut deck, settings)
+            .into_iter()
+            .map(|snapshot| area::create_area(snapshot, settings))
+            .collect::<Vec<_>>();
 
         let stock = area::stock::UnselectedStock::create(deck, settings);
         let talon = area::talon::UnselectedTalon::create(vec![], 0);
 
-        let mut foundation_areas = Suit::values()
-            .map(|suit| area::foundation::UnselectedFoundation::create(suit, vec![], settings))
+        let mut foundation_areas = foundation_suits(settings)
+            .map(|(index, suit)| {
+                area::foundation::UnselectedFoundation::create(index, suit, vec![], settings)
+            })
             .collect::<Vec<_>>();
 
         let mut areas: Vec<Box<dyn area::UnselectedArea>> = vec![stock, talon];
@@ -106,20 +101,33 @@ where
         areas.append(&mut tableaux_areas);
 
         let areas = area_list::AreaList::new(areas).expect("Unable to create AreaList");
-        Game::new(areas)
+        Game::new(areas, seed, settings)
     }
 }
 
+/// The index and suit of each foundation: one for each suit in each deck.
+fn foundation_suits(settings: &settings::GameSettings) -> impl Iterator<Item = (u8, Suit)> {
+    (0..settings.deck_count)
+        .flat_map(|deck| Suit::values().map(move |suit| (deck * SUIT_LEN + u8::from(suit), suit)))
+}
+
 trait Shuffle {
-    fn create_deck() -> Vec<Card>;
+    fn new_seed(&self) -> Option<u64>;
+    /// Create the cards of `deck_count` decks, shuffled from the seed if this shuffle uses one.
+    fn create_deck(&self, seed: Option<u64>, deck_count: u8) -> Vec<Card>;
 }
 
 #[derive(Debug)]
 struct InOrderShuffle;
 
 impl Shuffle for InOrderShuffle {
-    fn create_deck() -> Vec<Card> {
-        Suit::values()
+    fn new_seed(&self) -> Option<u64> {
+        None
+    }
+
+    fn create_deck(&self, _seed: Option<u64>, deck_count: u8) -> Vec<Card> {
+        (0..deck_count)
+            .flat_map(|_| Suit::values())
             .flat_map(|suit| Rank::values().map(move |rank| Card { rank, suit }))
             .collect::<Vec<_>>()
     }
@@ -129,9 +137,62 @@ impl Shuffle for InOrderShuffle {
 struct RandomShuffle;
 
 impl Shuffle for RandomShuffle {
-    fn create_deck() -> Vec<Card> {
-        let mut deck = InOrderShuffle::create_deck();
-        deck.shuffle(&mut thread_rng());
+    fn new_seed(&self) -> Option<u64> {
+        // Keep seeds short enough that players can easily read them off and share them.
+        Some(u64::from(thread_rng().gen::<u32>()))
+    }
+
+    fn create_deck(&self, seed: Option<u64>, deck_count: u8) -> Vec<Card> {
+        let mut deck = InOrderShuffle.create_deck(None, deck_count);
+
+        if let Some(seed) = seed {
+            shuffle_with_seed(&mut deck, seed);
+        }
+
         deck
     }
 }
+
+#[derive(Debug)]
+struct SeededShuffle(u64);
+
+impl Shuffle for SeededShuffle {
+    fn new_seed(&self) -> Option<u64> {
+        Some(self.0)
+    }
+
+    fn create_deck(&self, seed: Option<u64>, deck_count: u8) -> Vec<Card> {
+        RandomShuffle.create_deck(seed, deck_count)
+    }
+}
+
+/// Shuffle the deck deterministically from a seed.
+///
+/// The deal for a seed has to stay the same from release to release, or else saved and shared
+/// seeds would stop meaning anything. So nothing here leans on `rand`'s own algorithms, which may
+/// change between versions: neither `SliceRandom::shuffle` nor the `Uniform` sampling behind
+/// `gen_range`. Instead we run a plain Fisher-Yates shuffle on the raw output of ChaCha8, which is
+/// fixed for a given seed, and pick each index with [`index_below`]. The deal for a known seed is
+/// pinned by a test, so any change to it shows up there.
+fn shuffle_with_seed(deck: &mut [Card], seed: u64) {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+    for index in (1..deck.len()).rev() {
+        let other_index = index_below(&mut rng, index as u64 + 1) as usize;
+        deck.swap(index, other_index);
+    }
+}
+
+/// Pick a number from `0` up to, but not including, `bound`. Taking the remainder straight away
+/// would favour the lower numbers, so any draw from the incomplete block at the very top of the
+/// `u64` range is thrown away and drawn again.
+fn index_below(rng: &mut impl RngCore, bound: u64) -> u64 {
+    let zone = u64::MAX - u64::MAX % bound;
+
+    loop {
+        let value = rng.next_u64();
+        if value < zone {
+            return value % bound;
+        }
+    }
+}
@@ -1,30 +1,148 @@
-use std::borrow::Borrow;
+use std::{
+    borrow::Borrow,
+    time::{Duration, Instant},
+};
 
 use log::debug;
+use serde::{Deserialize, Serialize};
 
 use super::{
-    area::{Area, AreaId},
-    area_list::AreaList,
-    card::{Rank, Suit},
+    area::{self, Area, AreaId, Rejection},
+    area_list::{self, AreaList, AreaListSnapshot},
+    card::Rank,
+    hint::{self, Hint},
+    score::{Score, Scoring},
+    settings::GameSettings,
     stack::Stack,
 };
 
+/// The state of a [game](Game), detached from its areas so that it can be saved and resumed later.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GameSnapshot {
+    pub areas: AreaListSnapshot,
+    pub last_area: AreaId,
+    pub seed: Option<u64>,
+    /// How long the game had been played when it was saved
+    #[serde(default)]
+    pub elapsed: Duration,
+    /// How many actions had changed something when the game was saved
+    #[serde(default)]
+    pub move_count: usize,
+}
+
 #[derive(Debug)]
 pub struct Game {
     pub areas: AreaList,
     pub last_area: AreaId,
+    /// The seed the deck was shuffled with, if it was shuffled. Dealing the same seed again deals
+    /// the same game.
+    pub seed: Option<u64>,
+    /// The move currently suggested to the player, if they asked for one. Any other action clears
+    /// it.
+    pub hint: Option<Hint>,
+    /// Why the last action didn't happen, if it's worth telling the player. Any other action
+    /// clears it.
+    pub message: Option<String>,
+    pub score: Score,
+    /// How many actions have changed something, e.g., moved the selection or some cards
+    pub move_count: usize,
+    /// Whether safe cards are moved to the foundations after every action
+    auto_move_to_foundation: bool,
+    /// Whether every card was dealt face up, including the stock
+    open_deck: bool,
+    scoring: Scoring,
+    /// How long the game had been played before the clock was last started
+    elapsed_before_start: Duration,
+    /// When the clock was last started, i.e., when the game was dealt or resumed, or when play
+    /// went back to it. `None` while the clock is paused.
+    start_time: Option<Instant>,
 }
 
 impl Game {
-    pub fn new(areas: AreaList) -> Game {
+    pub fn new(areas: AreaList, seed: Option<u64>, settings: &GameSettings) -> Game {
         let last_area = areas.selected().id();
 
-        Game { areas, last_area }
+        let mut game = Game {
+            areas,
+            last_area,
+            seed,
+            hint: None,
+            message: None,
+            score: Score::default(),
+            move_count: 0,
+            auto_move_to_foundation: settings.auto_move_to_foundation,
+            open_deck: settings.open_deck,
+            scoring: Scoring::new(settings),
+            elapsed_before_start: Duration::default(),
+            start_time: Some(Instant::now()),
+        };
+
+        // Vegas scoring charges for the deal before any moves are made.
+        game.update_score();
+        game
+    }
+
+    pub fn from_snapshot(
+        snapshot: GameSnapshot,
+        settings: &GameSettings,
+    ) -> area_list::Result<Game> {
+        let areas = AreaList::from_snapshot(snapshot.areas, settings)?;
+
+        let mut game = Game {
+            areas,
+            last_area: snapshot.last_area,
+            seed: snapshot.seed,
+            hint: None,
+            message: None,
+            score: Score::default(),
+            move_count: snapshot.move_count,
+            auto_move_to_foundation: settings.auto_move_to_foundation,
+            open_deck: settings.open_deck,
+            scoring: Scoring::new(settings),
+            elapsed_before_start: snapshot.elapsed,
+            start_time: Some(Instant::now()),
+        };
+
+        game.update_score();
+        Ok(game)
+    }
+
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            areas: self.areas.snapshot(),
+            last_area: self.last_area,
+            seed: self.seed,
+            elapsed: self.elapsed(),
+            move_count: self.move_count,
+        }
+    }
+
+    /// How long the game has been played, including any time before it was saved and resumed.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed_before_start
+            + self
+                .start_time
+                .map_or_else(Duration::default, |start_time| start_time.elapsed())
+    }
+
+    /// Stop the clock, e.g., while the player is reading the help rather than playing.
+    pub fn pause(&mut self) {
+        if let Some(start_time) = self.start_time.take() {
+            self.elapsed_before_start += start_time.elapsed();
+        }
+    }
+
+    /// Start the clock again after a [pause](Game::pause).
+    pub fn resume(&mut self) {
+        if self.start_time.is_none() {
+            self.start_time = Some(Instant::now());
+        }
     }
 
     pub fn is_win(&self) -> bool {
-        Suit::values()
-            .flat_map(|suit| self.areas.get_by_area_id(AreaId::Foundation(suit)))
+        self.areas
+            .iter()
+            .filter(|area| matches!(area.id(), AreaId::Foundation(_)))
             .all(|foundation| {
                 let held = foundation.is_held();
                 let complete = foundation
@@ -44,8 +162,118 @@ impl Game {
         self.areas.get_by_area_id(area_id).ok().map(Area::as_stack)
     }
 
+    pub fn is_open_deck(&self) -> bool {
+        self.open_deck
+    }
+
+    /// How many more passes the player may make through an area, if it's limited. Only the stock
+    /// is ever limited.
+    pub fn passes_remaining(&self, area_id: AreaId) -> Option<usize> {
+        self.areas
+            .get_by_area_id(area_id)
+            .ok()
+            .and_then(Area::passes_remaining)
+    }
+
+    /// Which pass through the stock the player is on, starting from 1.
+    pub fn stock_pass(&self) -> usize {
+        self.areas.restock_count() + 1
+    }
+
+    /// How many passes through the stock the player gets in all, if it's limited.
+    pub fn stock_pass_limit(&self) -> Option<usize> {
+        self.passes_remaining(AreaId::Stock)
+            .map(|passes_remaining| self.stock_pass() + passes_remaining)
+    }
+
     pub fn apply_action(&mut self, action: Action) -> Vec<AreaId> {
-        action.borrow().apply(self)
+        self.message = None;
+
+        let old_hint_area_ids = if action == Action::Hint {
+            vec![]
+        } else {
+            self.clear_hint()
+        };
+
+        let mut area_ids = action.borrow().apply(self);
+
+        if !area_ids.is_empty() {
+            self.move_count += 1;
+        }
+
+        // Don't move any cards after undoing or redoing, or else the player could never undo an
+        // automatic move.
+        if self.auto_move_to_foundation && action.allows_auto_move() {
+            area_ids.extend(
+                self.areas
+                    .auto_move_to_foundation()
+                    .unwrap_or_else(|error| {
+                        debug!("Unable to move cards to foundation: {}", error);
+                        vec![]
+                    }),
+            );
+        }
+
+        area_ids.extend(old_hint_area_ids);
+        self.update_score();
+        area_ids
+    }
+
+    /// Whether the game is as good as won: the stock and talon are empty, and every card left in
+    /// the tableaux is face up, so the cards only have to be played up to the foundations in order.
+    pub fn can_auto_complete(&self) -> bool {
+        let stock_and_talon_empty = [AreaId::Stock, AreaId::Talon].iter().all(|&area_id| {
+            self.areas
+                .get_by_area_id(area_id)
+                .map(|area| area.peek_top_card().is_none())
+                .unwrap_or(true)
+        });
+
+        let tableaux_face_up = self.areas.iter().all(|area| match area.id() {
+            AreaId::Tableaux(_) => {
+                let stack = area.as_stack();
+                stack.details.face_up_len == stack.cards.len()
+            }
+            _ => true,
+        });
+
+        !self.areas.selected().is_held()
+            && stock_and_talon_empty
+            && tableaux_face_up
+            && !self.is_win()
+    }
+
+    /// Play one card to its foundation as part of auto-completing the game, returning the areas
+    /// that changed. Returns no area ids once there's nothing left to play.
+    pub fn auto_complete_step(&mut self) -> Vec<AreaId> {
+        let mut area_ids = self.clear_hint();
+
+        area_ids.extend(self.areas.step_to_foundation().unwrap_or_else(|error| {
+            debug!("Unable to move card to foundation: {}", error);
+            vec![]
+        }));
+
+        self.update_score();
+        area_ids
+    }
+
+    /// Rescore the moves made so far, and award the time bonus as soon as the game is won.
+    fn update_score(&mut self) {
+        self.score.points = self.scoring.points(self.areas.history().moves());
+
+        if !self.is_win() {
+            self.score.time_bonus = None;
+        } else if self.score.time_bonus.is_none() {
+            self.score.time_bonus = Some(self.scoring.time_bonus(self.elapsed()));
+        }
+    }
+
+    /// Forget the current hint, returning its areas so they can be redrawn without it.
+    fn clear_hint(&mut self) -> Vec<AreaId> {
+        self.hint
+            .take()
+            .map(|hint| hint.area_ids())
+            .unwrap_or_default()
     }
 }
 
@@ -60,9 +288,26 @@ pub enum Action {
     SelectLess,
     Activate,
     ReturnHeld,
+    Undo,
+    Redo,
+    /// Suggest a move, or the next one if a move is already suggested.
+    Hint,
 }
 
 impl Action {
+    fn allows_auto_move(self) -> bool {
+        !matches!(self, Action::Undo | Action::Redo | Action::Hint)
+    }
+
+    /// Whether to tell the player why the action didn't happen. Moving left or right just skips
+    /// over areas that won't take the held cards, so there's nothing to explain.
+    fn reports_rejection(self) -> bool {
+        matches!(
+            self,
+            Action::MoveTo(_) | Action::MoveBack | Action::MoveToFoundation
+        )
+    }
+
     fn apply(self, game: &mut Game) -> Vec<AreaId> {
         match self {
             Action::MoveTo(area_id) => {
@@ -74,7 +319,12 @@ impl Action {
                 self.make_first_valid_move(game, moves)
             }
             Action::MoveToFoundation => {
-                let moves = Suit::values().map(AreaId::Foundation);
+                let moves = game
+                    .areas
+                    .iter()
+                    .map(Area::id)
+                    .filter(|area_id| matches!(area_id, AreaId::Foundation(_)))
+                    .collect::<Vec<_>>();
                 self.make_first_valid_move(game, moves)
             }
             Action::MoveLeft => {
@@ -103,12 +353,48 @@ impl Action {
             }),
             Action::Activate => game.areas.activate_selected().unwrap_or_else(|error| {
                 debug!("Unable to activate: {}", error);
+                game.message = rejection_message(&error);
                 vec![]
             }),
             Action::ReturnHeld => game.areas.return_held().unwrap_or_else(|error| {
                 debug!("Unable to return held: {}", error);
                 vec![]
             }),
+            Action::Undo => game.areas.undo().unwrap_or_else(|error| {
+                debug!("Unable to undo: {}", error);
+                vec![]
+            }),
+            Action::Redo => game.areas.redo().unwrap_or_else(|error| {
+                debug!("Unable to redo: {}", error);
+                vec![]
+            }),
+            Action::Hint => {
+                // The areas can't tell us what they'd accept while they're holding cards.
+                let mut area_ids = game.areas.return_held().unwrap_or_else(|error| {
+                    debug!("Unable to return held: {}", error);
+                    vec![]
+                });
+
+                let hints = hint::hints(&game.areas);
+
+                // Cycle to the hint after the current one. If the current one isn't among the
+                // hints anymore, start over from the best hint.
+                let next_index = game
+                    .hint
+                    .and_then(|current| hints.iter().position(|&hint| hint == current))
+                    .map_or(0, |index| index + 1);
+                let next_hint = hints.get(next_index).or_else(|| hints.first()).copied();
+
+                if let Some(old_hint) = game.hint {
+                    area_ids.extend(old_hint.area_ids());
+                }
+                if let Some(next_hint) = next_hint {
+                    area_ids.extend(next_hint.area_ids());
+                }
+
+                game.hint = next_hint;
+                area_ids
+            }
         }
     }
 
@@ -117,6 +403,7 @@ impl Action {
         I: IntoIterator<Item = AreaId>,
     {
         let new_last_area = game.areas.selected().id();
+        let mut errors = vec![];
 
         for new_area_id in moves {
             debug!("Attempting to move selection to {:?}", new_area_id);
@@ -128,10 +415,44 @@ impl Action {
                 }
                 Err(error) => {
                     debug!("Unable to move to {:?}: {}", new_area_id, error);
+                    errors.push(error);
                 }
             }
         }
 
+        if self.reports_rejection() {
+            // A foundation of the wrong suit says the least about why the cards didn't go, so only
+            // report that if nothing else turned them away.
+            let error = errors
+                .iter()
+                .find(|error| !is_wrong_suit(error))
+                .or_else(|| errors.first());
+            game.message = error.and_then(rejection_message);
+        }
+
         vec![]
     }
 }
+
+/// Explain to the player why an area turned down the action, if it's worth explaining.
+fn rejection_message(error: &area_list::Error) -> Option<String> {
+    match error {
+        area_list::Error::SelectionError { area_id, source }
+        | area_list::Error::UnableToActivate { area_id, source } => {
+            source.rejection_message(*area_id)
+        }
+        _ => None,
+    }
+}
+
+fn is_wrong_suit(error: &area_list::Error) -> bool {
+    matches!(
+        error,
+        area_list::Error::SelectionError {
+            source: area::Error::InvalidCard {
+                rejection: Rejection::WrongSuit { .. }
+            },
+            ..
+        }
+    )
+}
@@ -0,0 +1,134 @@
+//! Module for suggesting moves to a player who is stuck.
+
+use super::{
+    area::{Area, AreaId, Held},
+    area_list::AreaList,
+};
+
+/// A suggested move: pick up the top `len` cards of the source area and put them down on the
+/// target area. Two special cases don't move cards between areas: a hint from the stock to the
+/// talon means drawing from the stock (or restocking it), and a hint whose source and target are
+/// the same tableaux means turning over its top card.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Hint {
+    pub source: AreaId,
+    pub target: AreaId,
+    pub len: usize,
+}
+
+impl Hint {
+    pub fn area_ids(&self) -> Vec<AreaId> {
+        if self.source == self.target {
+            vec![self.source]
+        } else {
+            vec![self.source, self.target]
+        }
+    }
+}
+
+/// Find every legal move in the game, best first. Moves are ranked by how much they're likely to
+/// help: playing to a foundation or turning over a card beats shuffling cards between tableaux,
+/// and drawing from the stock is always the last resort.
+///
+/// This only asks each area whether it would accept the cards, so the areas should not be holding
+/// any cards when it's called.
+pub fn hints(areas: &AreaList) -> Vec<Hint> {
+    let mut ranked_hints = vec![];
+
+    for source in areas.iter() {
+        let stack = source.as_stack();
+        let face_down_len = stack.cards.len() - stack.details.face_up_len;
+
+        if let AreaId::Tableaux(_) = source.id() {
+            if !stack.cards.is_empty() && stack.details.face_up_len == 0 {
+                ranked_hints.push((0, reveal_hint(source)));
+            }
+        }
+
+        for len in 1..=source.movable_len() {
+            let held = Held {
+                source: source.id(),
+                cards: stack.cards[stack.cards.len() - len..].to_vec(),
+            };
+
+            for target in areas.iter() {
+                if !is_hint_target(source, target) || target.accepts_cards(&held).is_err() {
+                    continue;
+                }
+
+                let target_is_empty = target.peek_top_card().is_none();
+
+                let rank = match (source.id(), target.id()) {
+                    (_, AreaId::Foundation(_)) => 0,
+                    (AreaId::Tableaux(_), _) if len == stack.details.face_up_len => {
+                        if face_down_len > 0 {
+                            1
+                        } else if target_is_empty {
+                            // Moving a whole pile to an empty tableaux doesn't change anything.
+                            continue;
+                        } else {
+                            3
+                        }
+                    }
+                    (AreaId::Talon, _) => 2,
+                    (AreaId::Tableaux(_), _) => 3,
+                    _ => 4,
+                };
+
+                ranked_hints.push((
+                    rank,
+                    Hint {
+                        source: source.id(),
+                        target: target.id(),
+                        len,
+                    },
+                ));
+            }
+        }
+    }
+
+    let has_cards = |area_id| {
+        areas
+            .get_by_area_id(area_id)
+            .map(|area| area.peek_top_card().is_some())
+            .unwrap_or_default()
+    };
+    let can_restock = areas
+        .get_by_area_id(AreaId::Stock)
+        .map(|stock| stock.passes_remaining() != Some(0))
+        .unwrap_or_default();
+
+    let can_draw = has_cards(AreaId::Stock) || (has_cards(AreaId::Talon) && can_restock);
+
+    if can_draw {
+        ranked_hints.push((
+            5,
+            Hint {
+                source: AreaId::Stock,
+                target: AreaId::Talon,
+                len: 0,
+            },
+        ));
+    }
+
+    // The sort is stable, so moves of the same rank stay in board order.
+    ranked_hints.sort_by_key(|(rank, _)| *rank);
+    ranked_hints.into_iter().map(|(_, hint)| hint).collect()
+}
+
+fn reveal_hint(area: &dyn Area) -> Hint {
+    Hint {
+        source: area.id(),
+        target: area.id(),
+        len: 1,
+    }
+}
+
+/// Cards can only be moved onto the foundations and tableaux. The stock and talon take cards back
+/// from each other, but that's drawing, which gets its own hint.
+fn is_hint_target(source: &dyn Area, target: &dyn Area) -> bool {
+    match target.id() {
+        AreaId::Foundation(_) | AreaId::Tableaux(_) => source.id() != target.id(),
+        AreaId::Stock | AreaId::Talon => false,
+    }
+}
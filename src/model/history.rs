@@ -0,0 +1,83 @@
+//! Module for recording the moves made in a game so they can be undone and redone.
+
+use serde::{Deserialize, Serialize};
+
+use super::{area::AreaId, card::Card};
+
+/// A change to the cards in a game. Each move records enough to undo it and bring back the exact
+/// earlier state, including how many cards each area had spread out.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum Move {
+    /// Cards were picked up from the source area and put down on the target area.
+    Transfer {
+        source: AreaId,
+        target: AreaId,
+        cards: Vec<Card>,
+        /// The spread length of the source area before the cards were picked up
+        source_spread_len: usize,
+    },
+    /// Cards were drawn from the stock onto the talon.
+    Draw {
+        len: usize,
+        /// The spread length of the talon before the cards were drawn
+        talon_spread_len: usize,
+    },
+    /// The talon was flipped back over onto the stock.
+    Restock {
+        len: usize,
+        /// The spread length of the talon before it was flipped
+        talon_spread_len: usize,
+    },
+    /// The top card of a tableaux was turned face up.
+    Reveal { area_id: AreaId },
+}
+
+/// The moves made so far in a game, along with any moves that have been undone and can still be
+/// redone. Recording a new move forgets the undone moves.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct History {
+    done: Vec<Move>,
+    undone: Vec<Move>,
+}
+
+impl History {
+    pub fn new() -> History {
+        History::default()
+    }
+
+    pub fn record(&mut self, new_move: Move) {
+        self.done.push(new_move);
+        self.undone.clear();
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.done
+    }
+
+    pub fn last(&self) -> Option<&Move> {
+        self.done.last()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// Pops the most recent move so it can be undone. It will be offered again by
+    /// [`redo`](History::redo).
+    pub fn undo(&mut self) -> Option<Move> {
+        let undone_move = self.done.pop()?;
+        self.undone.push(undone_move.clone());
+        Some(undone_move)
+    }
+
+    /// Pops the most recently undone move so it can be made again.
+    pub fn redo(&mut self) -> Option<Move> {
+        let redone_move = self.undone.pop()?;
+        self.done.push(redone_move.clone());
+        Some(redone_move)
+    }
+}
@@ -0,0 +1,492 @@
+//! Module for the key bindings, i.e., which keys do what.
+//!
+//! Bindings start from one of the [presets](KeyPreset), and the settings can then give any command
+//! its own keys, or bind more keys to a command one at a time. Names in settings files aren't
+//! case-sensitive, so a key bound by name has to be written as, e.g., `shift-h` rather than `H` to
+//! tell it apart from `h`.
+
+use serde::{Deserialize, Serialize};
+use snafu::OptionExt;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    str::FromStr,
+};
+use termion::event::Key;
+
+use super::{game::Action, AreaId};
+
+/// How many tableaux can be picked with the digit keys, with 0 for the tenth
+static TABLEAUX_KEY_LEN: u8 = 10;
+
+/// How many foundations can be picked with the function keys
+static FOUNDATION_KEY_LEN: u8 = 8;
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Unknown key: {:?}", name))]
+    InvalidKeyName { name: String },
+
+    #[snafu(display("Unknown command: {:?}", name))]
+    InvalidCommand { name: String },
+
+    #[snafu(display("Key {} is bound to both {} and {}", key_name(*key), first, second))]
+    ConflictingBindings {
+        key: Key,
+        first: Command,
+        second: Command,
+    },
+}
+
+pub type Result<T, E = Error> = ::std::result::Result<T, E>;
+
+/// Something a key can do, on top of the game [actions](Action).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Command {
+    Action(Action),
+    NewGame,
+    ReplayGame,
+    AutoComplete,
+    Help,
+    Statistics,
+    Quit,
+}
+
+/// The name of every command that doesn't pick a particular area, as written in the settings.
+static COMMAND_NAMES: &[(&str, Command)] = &[
+    ("move-left", Command::Action(Action::MoveLeft)),
+    ("move-right", Command::Action(Action::MoveRight)),
+    ("select-more", Command::Action(Action::SelectMore)),
+    ("select-less", Command::Action(Action::SelectLess)),
+    ("stock", Command::Action(Action::MoveTo(AreaId::Stock))),
+    ("talon", Command::Action(Action::MoveTo(AreaId::Talon))),
+    ("foundation", Command::Action(Action::MoveToFoundation)),
+    ("back", Command::Action(Action::MoveBack)),
+    ("activate", Command::Action(Action::Activate)),
+    ("return-held", Command::Action(Action::ReturnHeld)),
+    ("undo", Command::Action(Action::Undo)),
+    ("redo", Command::Action(Action::Redo)),
+    ("hint", Command::Action(Action::Hint)),
+    ("new-game", Command::NewGame),
+    ("replay", Command::ReplayGame),
+    ("auto-complete", Command::AutoComplete),
+    ("help", Command::Help),
+    ("statistics", Command::Statistics),
+    ("quit", Command::Quit),
+];
+
+impl FromStr for Command {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        if let Some(&(_, command)) = COMMAND_NAMES.iter().find(|(other, _)| *other == name) {
+            return Ok(command);
+        }
+
+        // Commands to go to a particular tableaux or foundation are numbered from 1, like the keys.
+        let numbered_area = |prefix: &str, area_id: fn(u8) -> AreaId| {
+            let number = name.strip_prefix(prefix)?.parse::<u8>().ok()?;
+            let index = number.checked_sub(1)?;
+            Some(Command::Action(Action::MoveTo(area_id(index))))
+        };
+
+        numbered_area("tableaux-", AreaId::Tableaux)
+            .or_else(|| numbered_area("foundation-", AreaId::Foundation))
+            .context(InvalidCommand { name })
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Action(Action::MoveTo(AreaId::Tableaux(index))) => {
+                write!(fmt, "tableaux-{}", u16::from(*index) + 1)
+            }
+            Command::Action(Action::MoveTo(AreaId::Foundation(index))) => {
+                write!(fmt, "foundation-{}", u16::from(*index) + 1)
+            }
+            _ => {
+                let name = COMMAND_NAMES
+                    .iter()
+                    .find(|(_, command)| command == self)
+                    .map_or("unknown", |(name, _)| name);
+                write!(fmt, "{}", name)
+            }
+        }
+    }
+}
+
+/// The bindings to start from, before the settings add their own.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum KeyPreset {
+    /// `h`, `j`, `k`, and `l` to move, as in Vim, as well as the arrow keys
+    Vim,
+    /// Only the arrow keys to move
+    Arrows,
+    /// The digits to move and play cards, laid out for the number pad
+    Numpad,
+}
+
+impl Default for KeyPreset {
+    fn default() -> Self {
+        KeyPreset::Vim
+    }
+}
+
+/// The keys for one command, either just the one or a list.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum KeyNames {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyNames {
+    fn names(&self) -> &[String] {
+        match self {
+            KeyNames::One(name) => std::slice::from_ref(name),
+            KeyNames::Many(names) => names,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct KeySettings {
+    pub preset: KeyPreset,
+    /// Keys to use while playing. Keys listed under a command name replace the preset's keys for
+    /// that command, and a command listed under a key name gets that key as well as its own.
+    pub playing: BTreeMap<String, KeyNames>,
+    /// Keys to use when the game has been won, in the same form as `playing`
+    pub win: BTreeMap<String, KeyNames>,
+}
+
+impl KeySettings {
+    pub fn playing_keymap(&self) -> Result<Keymap> {
+        Keymap::new(playing_preset(self.preset), &self.playing)
+    }
+
+    pub fn win_keymap(&self) -> Result<Keymap> {
+        Keymap::new(win_preset(), &self.win)
+    }
+
+    /// Check that every key and command can be read, and that no key is bound twice.
+    pub fn validate(&self) -> Result<()> {
+        self.playing_keymap()?;
+        self.win_keymap()?;
+        Ok(())
+    }
+}
+
+/// A command and the keys that do it.
+#[derive(Clone, Debug)]
+pub struct Binding {
+    pub command: Command,
+    pub keys: Vec<Key>,
+}
+
+/// Every command that has keys, in the order they were bound, with the preset's commands first.
+#[derive(Clone, Debug, Default)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+}
+
+impl Keymap {
+    fn new(preset: Vec<Binding>, settings: &BTreeMap<String, KeyNames>) -> Result<Keymap> {
+        let mut keymap = Keymap { bindings: preset };
+
+        for (name, key_names) in settings {
+            match (name.parse(), key_names) {
+                (Ok(command), _) => {
+                    let keys = key_names
+                        .names()
+                        .iter()
+                        .map(|name| parse_key(name))
+                        .collect::<Result<Vec<_>>>()?;
+
+                    if let Some(binding) = keymap.binding_mut(command) {
+                        binding.keys = keys;
+                    } else {
+                        keymap.bindings.push(Binding { command, keys });
+                    }
+                }
+                // Otherwise the setting binds a key to a command, e.g., `ctrl-z = "undo"`.
+                (Err(error), KeyNames::One(command_name)) => {
+                    let key = parse_key(name).map_err(|_| error)?;
+                    let command = command_name.parse()?;
+
+                    if let Some(binding) = keymap.binding_mut(command) {
+                        if !binding.keys.contains(&key) {
+                            binding.keys.push(key);
+                        }
+                    } else {
+                        keymap.bindings.push(Binding {
+                            command,
+                            keys: vec![key],
+                        });
+                    }
+                }
+                (Err(error), KeyNames::Many(_)) => return Err(error),
+            }
+        }
+
+        keymap.check_conflicts()?;
+        Ok(keymap)
+    }
+
+    pub fn bindings(&self) -> &[Binding] {
+        &self.bindings
+    }
+
+    pub fn command_for(&self, key: Key) -> Option<Command> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.keys.contains(&key))
+            .map(|binding| binding.command)
+    }
+
+    pub fn keys_for(&self, command: Command) -> &[Key] {
+        self.bindings
+            .iter()
+            .find(|binding| binding.command == command)
+            .map_or(&[], |binding| &binding.keys)
+    }
+
+    fn binding_mut(&mut self, command: Command) -> Option<&mut Binding> {
+        self.bindings
+            .iter_mut()
+            .find(|binding| binding.command == command)
+    }
+
+    fn check_conflicts(&self) -> Result<()> {
+        let mut commands_by_key = HashMap::new();
+
+        for binding in &self.bindings {
+            for &key in &binding.keys {
+                if let Some(&first) = commands_by_key.get(&key) {
+                    ensure!(
+                        first == binding.command,
+                        ConflictingBindings {
+                            key,
+                            first,
+                            second: binding.command,
+                        }
+                    );
+                }
+
+                commands_by_key.insert(key, binding.command);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Read a key as written in the settings: a single character, which is case-sensitive, or the name
+/// of a key, e.g., `enter`, `left`, `f1`, `ctrl-r`, or `shift-h` for `H`, which isn't.
+pub fn parse_key(name: &str) -> Result<Key> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(Key::Char(c));
+    }
+
+    let lowercase_name = name.to_lowercase();
+    let single_char = |rest: &str| {
+        let mut chars = rest.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    };
+
+    let key = match lowercase_name.as_str() {
+        "space" => Some(Key::Char(' ')),
+        "enter" | "return" => Some(Key::Char('\n')),
+        "tab" => Some(Key::Char('\t')),
+        "esc" | "escape" => Some(Key::Esc),
+        "backspace" => Some(Key::Backspace),
+        "delete" => Some(Key::Delete),
+        "insert" => Some(Key::Insert),
+        "home" => Some(Key::Home),
+        "end" => Some(Key::End),
+        "pageup" => Some(Key::PageUp),
+        "pagedown" => Some(Key::PageDown),
+        "left" => Some(Key::Left),
+        "right" => Some(Key::Right),
+        "up" => Some(Key::Up),
+        "down" => Some(Key::Down),
+        other => {
+            if let Some(rest) = other.strip_prefix("ctrl-") {
+                single_char(rest).map(Key::Ctrl)
+            } else if let Some(rest) = other.strip_prefix("shift-") {
+                single_char(rest)
+                    .filter(char::is_ascii_alphabetic)
+                    .map(|c| Key::Char(c.to_ascii_uppercase()))
+            } else if other.starts_with("alt-") {
+                // Keep the case of the character, since alt-x and alt-X are different keys.
+                single_char(&name[4..]).map(Key::Alt)
+            } else if let Some(rest) = other.strip_prefix('f') {
+                rest.parse()
+                    .ok()
+                    .filter(|number| (1..=12).contains(number))
+                    .map(Key::F)
+            } else {
+                None
+            }
+        }
+    };
+
+    key.context(InvalidKeyName { name })
+}
+
+/// The name of a key, as it would be written in the settings.
+pub fn key_name(key: Key) -> String {
+    match key {
+        Key::Char(' ') => "space".to_string(),
+        Key::Char('\n') => "enter".to_string(),
+        Key::Char('\t') => "tab".to_string(),
+        Key::Char(c) => c.to_string(),
+        Key::Ctrl(c) => format!("ctrl-{}", c),
+        Key::Alt(c) => format!("alt-{}", c),
+        Key::F(number) => format!("f{}", number),
+        Key::Esc => "esc".to_string(),
+        Key::Backspace => "backspace".to_string(),
+        Key::Delete => "delete".to_string(),
+        Key::Insert => "insert".to_string(),
+        Key::Home => "home".to_string(),
+        Key::End => "end".to_string(),
+        Key::PageUp => "pageup".to_string(),
+        Key::PageDown => "pagedown".to_string(),
+        Key::Left => "left".to_string(),
+        Key::Right => "right".to_string(),
+        Key::Up => "up".to_string(),
+        Key::Down => "down".to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+fn bind(command: Command, keys: &[Key]) -> Binding {
+    Binding {
+        command,
+        keys: keys.to_vec(),
+    }
+}
+
+fn bind_action(action: Action, keys: &[Key]) -> Binding {
+    bind(Command::Action(action), keys)
+}
+
+fn playing_preset(preset: KeyPreset) -> Vec<Binding> {
+    let mut bindings = match preset {
+        KeyPreset::Vim => vec![
+            bind_action(Action::MoveLeft, &[Key::Char('h'), Key::Left]),
+            bind_action(Action::SelectLess, &[Key::Char('j'), Key::Down]),
+            bind_action(Action::SelectMore, &[Key::Char('k'), Key::Up]),
+            bind_action(Action::MoveRight, &[Key::Char('l'), Key::Right]),
+        ],
+        KeyPreset::Arrows => vec![
+            bind_action(Action::MoveLeft, &[Key::Left]),
+            bind_action(Action::SelectLess, &[Key::Down]),
+            bind_action(Action::SelectMore, &[Key::Up]),
+            bind_action(Action::MoveRight, &[Key::Right]),
+        ],
+        KeyPreset::Numpad => return numpad_preset(),
+    };
+
+    bindings.extend(vec![
+        bind_action(Action::MoveTo(AreaId::Stock), &[Key::Char('s')]),
+        bind_action(Action::MoveTo(AreaId::Talon), &[Key::Char('t')]),
+        bind_action(Action::MoveToFoundation, &[Key::Char('f')]),
+        bind_action(Action::MoveBack, &[Key::Char('-')]),
+    ]);
+
+    bindings.extend((0..TABLEAUX_KEY_LEN).map(|index| {
+        let digit = std::char::from_digit((u32::from(index) + 1) % 10, 10).unwrap_or_default();
+        bind_action(Action::MoveTo(AreaId::Tableaux(index)), &[Key::Char(digit)])
+    }));
+
+    bindings.extend((0..FOUNDATION_KEY_LEN).map(|index| {
+        bind_action(
+            Action::MoveTo(AreaId::Foundation(index)),
+            &[Key::F(index + 1)],
+        )
+    }));
+
+    bindings.extend(vec![
+        bind_action(Action::Activate, &[Key::Char(' '), Key::Char('\n')]),
+        bind_action(Action::ReturnHeld, &[Key::Esc]),
+        bind_action(Action::Undo, &[Key::Char('u')]),
+        bind_action(Action::Redo, &[Key::Ctrl('r')]),
+        bind_action(Action::Hint, &[Key::Char('H')]),
+    ]);
+
+    bindings.extend(common_playing_bindings(&[Key::Char('a')]));
+    bindings
+}
+
+/// The digits take the place of the letters, so the whole game can be played from the number pad.
+/// There are no keys for each tableaux, since the digits are spoken for.
+fn numpad_preset() -> Vec<Binding> {
+    let mut bindings = vec![
+        bind_action(Action::MoveLeft, &[Key::Char('4'), Key::Left]),
+        bind_action(Action::SelectLess, &[Key::Char('2'), Key::Down]),
+        bind_action(Action::SelectMore, &[Key::Char('8'), Key::Up]),
+        bind_action(Action::MoveRight, &[Key::Char('6'), Key::Right]),
+        bind_action(
+            Action::MoveTo(AreaId::Stock),
+            &[Key::Char('7'), Key::Char('s')],
+        ),
+        bind_action(
+            Action::MoveTo(AreaId::Talon),
+            &[Key::Char('9'), Key::Char('t')],
+        ),
+        bind_action(Action::MoveToFoundation, &[Key::Char('1'), Key::Char('f')]),
+        bind_action(Action::MoveBack, &[Key::Char('3'), Key::Char('-')]),
+    ];
+
+    bindings.extend((0..FOUNDATION_KEY_LEN).map(|index| {
+        bind_action(
+            Action::MoveTo(AreaId::Foundation(index)),
+            &[Key::F(index + 1)],
+        )
+    }));
+
+    bindings.extend(vec![
+        bind_action(
+            Action::Activate,
+            &[
+                Key::Char('5'),
+                Key::Char('0'),
+                Key::Char(' '),
+                Key::Char('\n'),
+            ],
+        ),
+        bind_action(Action::ReturnHeld, &[Key::Char('.'), Key::Esc]),
+        bind_action(Action::Undo, &[Key::Char('/'), Key::Char('u')]),
+        bind_action(Action::Redo, &[Key::Char('*'), Key::Ctrl('r')]),
+        bind_action(Action::Hint, &[Key::Char('H')]),
+    ]);
+
+    bindings.extend(common_playing_bindings(&[Key::Char('+'), Key::Char('a')]));
+    bindings
+}
+
+/// The commands that aren't about moving cards, which every preset shares.
+fn common_playing_bindings(auto_complete_keys: &[Key]) -> Vec<Binding> {
+    vec![
+        bind(Command::AutoComplete, auto_complete_keys),
+        bind(Command::ReplayGame, &[Key::Char('R')]),
+        bind(Command::Statistics, &[Key::Char('S')]),
+        bind(Command::Help, &[Key::Char('?')]),
+        bind(Command::Quit, &[Key::Char('q')]),
+    ]
+}
+
+fn win_preset() -> Vec<Binding> {
+    vec![
+        bind(Command::NewGame, &[Key::Char('y')]),
+        bind(Command::ReplayGame, &[Key::Char('r')]),
+        bind(Command::Statistics, &[Key::Char('s')]),
+        bind(Command::Quit, &[Key::Char('n')]),
+    ]
+}
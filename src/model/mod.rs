@@ -10,5 +10,13 @@ pub mod area_list;
 pub mod card;
 pub mod dealer;
 pub mod game;
+pub mod hint;
+pub mod history;
+pub mod keys;
+pub mod rules;
+pub mod save;
+pub mod score;
 pub mod settings;
+pub mod solver;
 pub mod stack;
+pub mod stats;
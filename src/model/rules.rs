@@ -0,0 +1,254 @@
+//! Module for the rules of the solitaire games that can be played on the board.
+//!
+//! The [areas](super::area) handle selecting, holding, and moving cards, which works the same way
+//! in every game. What differs from game to game is how the tableaux are dealt, which cards the
+//! tableaux and foundations will take, and what the stock does, so the areas ask the
+//! [rules](Rules) of the chosen [variant](Variant) about those.
+//!
+//! The rules only get to decide things an area can answer from its own cards, so they cover games
+//! played on the Klondike layout: Klondike itself and Yukon. Games that need areas of their own,
+//! like the free cells of FreeCell, or that depend on the rest of the board, like its supermove
+//! limit or Spider's removal of finished runs, aren't supported.
+
+use num_traits::ToPrimitive;
+use std::fmt;
+
+use super::{
+    area::{
+        Action, AreaId, AreaSnapshot, InvalidCard, NoPassesRemain, NothingToSelect, Rejection,
+        Result, TooManyCards,
+    },
+    card::{Card, Rank, Suit},
+    settings::{GameSettings, Variant},
+};
+
+pub trait Rules: fmt::Debug + Sync {
+    /// Deal the tableaux from the top of the deck, which is the end of the vector. Whatever is left
+    /// of the deck goes to the stock.
+    fn deal_tableaux(&self, deck: &mut Vec<Card>, settings: &GameSettings) -> Vec<AreaSnapshot>;
+
+    /// Check whether the held cards may be placed on a tableaux, given its top card, or `None` if
+    /// it's empty.
+    fn tableaux_accepts(&self, top: Option<&Card>, cards: &[Card]) -> Result;
+
+    /// Check whether the held cards may be placed on a foundation of the given suit, given its top
+    /// card, or `None` if it's empty.
+    fn foundation_accepts(&self, suit: Suit, top: Option<&Card>, cards: &[Card]) -> Result;
+
+    /// Decide what activating the stock does, given the state of the stock.
+    fn stock_action(&self, stock: &StockState) -> Result<Action>;
+}
+
+/// What the rules need to know about the stock to decide what activating it does.
+#[derive(Clone, Debug)]
+pub struct StockState {
+    /// How many cards are left in the stock
+    pub len: usize,
+    /// How many cards are drawn from the stock at a time
+    pub draw_len: usize,
+    /// How many times the player may go through the stock, or `None` if there's no limit
+    pub max_passes: Option<usize>,
+    /// How many passes are left after the current one, or `None` if there's no limit
+    pub passes_remaining: Option<usize>,
+}
+
+/// The rules of the given variant. Rules are stateless, so every area can share the same ones.
+pub fn rules_for(variant: Variant) -> &'static dyn Rules {
+    match variant {
+        Variant::Klondike => &KlondikeRules,
+        Variant::Yukon => &YukonRules,
+    }
+}
+
+/// Klondike: the tableaux are built down in alternating colors, kings go in empty tableaux, and the
+/// rest of the deck is drawn from the stock.
+#[derive(Debug)]
+struct KlondikeRules;
+
+impl Rules for KlondikeRules {
+    fn deal_tableaux(&self, deck: &mut Vec<Card>, settings: &GameSettings) -> Vec<AreaSnapshot> {
+        let len: usize = settings.tableaux_len.into();
+
+        let mut piles: Vec<Vec<Card>> = vec![vec![]; len];
+        let indexes = (0..len).flat_map(|level| level..len);
+
+        // The settings limit the piles to what the deck can fill, so it never runs out here.
+        for index in indexes {
+            piles[index].extend(deck.pop());
+        }
+
+        piles
+            .into_iter()
+            .enumerate()
+            .map(|(index, cards)| {
+                let revealed_len = if settings.open_deck {
+                    cards.len()
+                } else {
+                    cards.len().min(1)
+                };
+
+                AreaSnapshot {
+                    // TODO: Use a proper Result type instead of unwrap.
+                    id: AreaId::Tableaux(index.to_u8().unwrap()),
+                    cards,
+                    spread_len: revealed_len,
+                }
+            })
+            .collect()
+    }
+
+    fn tableaux_accepts(&self, top: Option<&Card>, cards: &[Card]) -> Result {
+        // The held cards have to be a run themselves, not just start with the right card.
+        for pair in cards.windows(2) {
+            let (card, next_card) = (&pair[0], &pair[1]);
+            ensure!(
+                next_card.rank.is_followed_by(card.rank) && next_card.color() != card.color(),
+                InvalidCard {
+                    rejection: Rejection::OutOfSequence {
+                        card: next_card.clone(),
+                        on: card.clone(),
+                    }
+                }
+            );
+        }
+
+        validate_tableaux_base(top, cards)
+    }
+
+    fn foundation_accepts(&self, suit: Suit, top: Option<&Card>, cards: &[Card]) -> Result {
+        if let [card] = cards {
+            ensure!(
+                suit == card.suit,
+                InvalidCard {
+                    rejection: Rejection::WrongSuit {
+                        card: card.clone(),
+                        suit,
+                    },
+                }
+            );
+
+            if let Some(foundation_card) = top {
+                // If there are already cards in this foundation, only accept the next card in
+                // sequence.
+                ensure!(
+                    foundation_card.rank.is_followed_by(card.rank),
+                    InvalidCard {
+                        rejection: Rejection::DoesNotFollow {
+                            card: card.clone(),
+                            top: Some(foundation_card.clone()),
+                        },
+                    }
+                );
+            } else {
+                // If there are no cards in this foundation yet, we have to start with the ace.
+                ensure!(
+                    card.rank == Rank::Ace,
+                    InvalidCard {
+                        rejection: Rejection::DoesNotFollow {
+                            card: card.clone(),
+                            top: None,
+                        },
+                    }
+                );
+            }
+
+            Ok(())
+        } else {
+            ensure!(
+                cards.is_empty(),
+                TooManyCards {
+                    message: "Expected only one card",
+                }
+            );
+            Ok(())
+        }
+    }
+
+    /// Draw from the stock while it has cards. Once it's empty, turn the talon back over onto it,
+    /// as long as another pass through it is allowed.
+    fn stock_action(&self, stock: &StockState) -> Result<Action> {
+        if stock.len > 0 {
+            return Ok(Action::Draw(stock.draw_len));
+        }
+
+        if let Some(max_passes) = stock.max_passes {
+            ensure!(
+                stock.passes_remaining != Some(0),
+                NoPassesRemain { max_passes }
+            );
+        }
+
+        Ok(Action::Restock)
+    }
+}
+
+/// Yukon: there's no stock. The deck is dealt out like Klondike, and then the rest of it is dealt
+/// face up onto every tableaux but the first. Any face-up cards can be moved together, whether or
+/// not they're in sequence, as long as the bottom card follows the Klondike rules.
+#[derive(Debug)]
+struct YukonRules;
+
+impl Rules for YukonRules {
+    fn deal_tableaux(&self, deck: &mut Vec<Card>, settings: &GameSettings) -> Vec<AreaSnapshot> {
+        let mut piles = KlondikeRules.deal_tableaux(deck, settings);
+
+        for index in (1..piles.len()).cycle() {
+            if let Some(card) = deck.pop() {
+                let pile = &mut piles[index];
+                pile.cards.push(card);
+                pile.spread_len += 1;
+            } else {
+                break;
+            }
+        }
+
+        piles
+    }
+
+    fn tableaux_accepts(&self, top: Option<&Card>, cards: &[Card]) -> Result {
+        validate_tableaux_base(top, cards)
+    }
+
+    fn foundation_accepts(&self, suit: Suit, top: Option<&Card>, cards: &[Card]) -> Result {
+        KlondikeRules.foundation_accepts(suit, top, cards)
+    }
+
+    /// The whole deck is dealt to the tableaux, so there's never anything to draw.
+    fn stock_action(&self, _stock: &StockState) -> Result<Action> {
+        NothingToSelect {
+            message: "There is no stock in Yukon",
+        }
+        .fail()
+    }
+}
+
+/// Check that the first of the held cards can go on a tableaux with the given top card: the next
+/// rank down in the other color, or a king if the tableaux is empty.
+fn validate_tableaux_base(top: Option<&Card>, cards: &[Card]) -> Result {
+    if let Some(card) = cards.first() {
+        if let Some(tableaux_card) = top {
+            ensure!(
+                card.rank.is_followed_by(tableaux_card.rank)
+                    && card.color() != tableaux_card.color(),
+                InvalidCard {
+                    rejection: Rejection::DoesNotFollow {
+                        card: card.clone(),
+                        top: Some(tableaux_card.clone()),
+                    }
+                }
+            );
+        } else {
+            ensure!(
+                card.rank == Rank::King,
+                InvalidCard {
+                    rejection: Rejection::DoesNotFollow {
+                        card: card.clone(),
+                        top: None,
+                    }
+                }
+            );
+        }
+    }
+
+    Ok(())
+}
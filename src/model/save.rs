@@ -0,0 +1,160 @@
+//! Module for saving an in-progress game when the player quits, and resuming it next time. The
+//! Vegas bankroll is kept here too, since it also carries over from one session to the next.
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use super::{
+    area_list,
+    game::{Game, GameSnapshot},
+    settings::{project_dirs, GameSettings},
+};
+
+static SAVE_FILE: &str = "saved-game.json";
+
+static BANKROLL_FILE: &str = "bankroll.json";
+
+/// Bump this whenever the save file format changes incompatibly. Saves from other versions are
+/// ignored rather than misread.
+static SAVE_VERSION: u32 = 2;
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Unable to find a data directory for saved games"))]
+    NoDataDirectory,
+
+    #[snafu(display("Unable to access save file {}: {}", path.display(), source))]
+    FileError { path: PathBuf, source: io::Error },
+
+    #[snafu(display("Unable to read save file {}: {}", path.display(), source))]
+    FormatError {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+
+    #[snafu(display("Unsupported save file version: {}", version))]
+    UnsupportedVersion { version: u32 },
+
+    #[snafu(display("Unable to restore saved game: {}", source))]
+    RestoreError { source: area_list::Error },
+}
+
+pub type Result<T, E = Error> = ::std::result::Result<T, E>;
+
+/// Just the version of a save file, so we can check it before reading the rest.
+#[derive(Debug, Deserialize)]
+struct SaveVersion {
+    version: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct SaveFile {
+    version: u32,
+    game: GameSnapshot,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct BankrollFile {
+    balance: i64,
+}
+
+fn data_file_path(file_name: &str) -> Result<PathBuf> {
+    let project_dirs = project_dirs().context(NoDataDirectory)?;
+
+    let mut path = project_dirs.data_dir().to_path_buf();
+    path.push(file_name);
+
+    Ok(path)
+}
+
+fn save_file_path() -> Result<PathBuf> {
+    data_file_path(SAVE_FILE)
+}
+
+fn write_data_file(path: &Path, contents: String) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context(FileError { path: parent })?;
+    }
+
+    fs::write(path, contents).context(FileError { path })
+}
+
+pub fn save_game(game: &Game) -> Result<()> {
+    let path = save_file_path()?;
+
+    let save_file = SaveFile {
+        version: SAVE_VERSION,
+        game: game.snapshot(),
+    };
+
+    let contents = serde_json::to_string(&save_file).context(FormatError { path: &path })?;
+    write_data_file(&path, contents)?;
+
+    info!("Saved game to {}", path.display());
+    Ok(())
+}
+
+/// Load the saved game, if there is one.
+pub fn load_game(settings: &GameSettings) -> Result<Option<Game>> {
+    let path = save_file_path()?;
+
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error).context(FileError { path }),
+    };
+
+    let SaveVersion { version } =
+        serde_json::from_str(&contents).context(FormatError { path: &path })?;
+    ensure!(version == SAVE_VERSION, UnsupportedVersion { version });
+
+    let save_file: SaveFile =
+        serde_json::from_str(&contents).context(FormatError { path: &path })?;
+    let game = Game::from_snapshot(save_file.game, settings).context(RestoreError)?;
+
+    info!("Resumed game from {}", path.display());
+    Ok(Some(game))
+}
+
+/// Remove the saved game, e.g., once it has been won.
+pub fn delete_saved_game() -> Result<()> {
+    let path = save_file_path()?;
+
+    match fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(error).context(FileError { path }),
+    }
+}
+
+/// Load the Vegas bankroll. A player who has never played Vegas starts with nothing.
+pub fn load_bankroll() -> Result<i64> {
+    let path = data_file_path(BANKROLL_FILE)?;
+
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(error) => return Err(error).context(FileError { path }),
+    };
+
+    let BankrollFile { balance } =
+        serde_json::from_str(&contents).context(FormatError { path: &path })?;
+
+    Ok(balance)
+}
+
+pub fn save_bankroll(balance: i64) -> Result<()> {
+    let path = data_file_path(BANKROLL_FILE)?;
+
+    let contents =
+        serde_json::to_string(&BankrollFile { balance }).context(FormatError { path: &path })?;
+    write_data_file(&path, contents)?;
+
+    info!("Saved bankroll of {} to {}", balance, path.display());
+    Ok(())
+}
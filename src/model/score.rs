@@ -0,0 +1,147 @@
+//! Module for scoring a game, either the classic (Windows) way or Vegas style.
+
+use std::{convert::TryFrom, time::Duration};
+
+use super::{
+    area::AreaId,
+    history::Move,
+    settings::{GameSettings, ScoringMode},
+};
+
+/// Games shorter than this don't earn a time bonus.
+static MIN_TIME_BONUS_DURATION: Duration = Duration::from_secs(30);
+
+/// The time bonus is this many points divided by the number of seconds the game took.
+static TIME_BONUS_NUMERATOR: u64 = 700_000;
+
+/// When drawing more than one card at a time, this many passes through the stock are free before
+/// restocking costs points.
+static FREE_PASSES: usize = 3;
+
+/// In Vegas scoring, what each deal costs for each deck dealt.
+static VEGAS_DEAL_COST: i32 = 52;
+
+/// In Vegas scoring, what each card played to a foundation pays.
+static VEGAS_FOUNDATION_CARD_PAYOUT: i32 = 5;
+
+/// The points a player has earned so far in a game.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Score {
+    /// Points earned (and lost) by moving cards
+    pub points: i32,
+    /// Points earned for winning quickly. Only awarded once the game is won.
+    pub time_bonus: Option<i32>,
+}
+
+impl Score {
+    pub fn total(&self) -> i32 {
+        self.points + self.time_bonus.unwrap_or_default()
+    }
+}
+
+/// The classic scoring rules:
+///
+/// | Event                          | Points                                     |
+/// |--------------------------------|--------------------------------------------|
+/// | Talon to tableaux              | 5                                          |
+/// | Talon to foundation            | 10                                         |
+/// | Tableaux to foundation         | 10                                         |
+/// | Turning over a tableaux card   | 5                                          |
+/// | Foundation back to tableaux    | −15                                        |
+/// | Restocking, drawing one card   | −100                                       |
+/// | Restocking, drawing more cards | −20, after the first three passes          |
+///
+/// The score never drops below zero. Winning in 30 seconds or more adds a time bonus of 700,000
+/// divided by the number of seconds the game took.
+///
+/// Vegas scoring instead starts each game at −52 for each deck, pays 5 for each card on the
+/// foundations, and never awards a time bonus.
+#[derive(Clone, Debug)]
+pub struct Scoring {
+    mode: ScoringMode,
+    draw_from_stock_len: usize,
+    deck_count: u8,
+}
+
+impl Scoring {
+    pub fn new(settings: &GameSettings) -> Scoring {
+        Scoring {
+            mode: settings.scoring,
+            draw_from_stock_len: settings.draw_from_stock_len,
+            deck_count: settings.deck_count,
+        }
+    }
+
+    /// Score the moves made in a game, in order. The score is computed from scratch so that undone
+    /// moves no longer count.
+    pub fn points(&self, moves: &[Move]) -> i32 {
+        match self.mode {
+            ScoringMode::Standard => self.standard_points(moves),
+            ScoringMode::Vegas => self.vegas_points(moves),
+        }
+    }
+
+    /// The bonus for winning a game that took the given time.
+    pub fn time_bonus(&self, elapsed: Duration) -> i32 {
+        if self.mode == ScoringMode::Vegas || elapsed < MIN_TIME_BONUS_DURATION {
+            0
+        } else {
+            i32::try_from(TIME_BONUS_NUMERATOR / elapsed.as_secs()).unwrap_or(i32::MAX)
+        }
+    }
+
+    fn standard_points(&self, moves: &[Move]) -> i32 {
+        let mut restock_count = 0;
+
+        moves.iter().fold(0, |points, game_move| {
+            let change = match *game_move {
+                Move::Transfer { source, target, .. } => match (source, target) {
+                    (AreaId::Talon, AreaId::Tableaux(_)) => 5,
+                    (AreaId::Talon, AreaId::Foundation(_)) => 10,
+                    (AreaId::Tableaux(_), AreaId::Foundation(_)) => 10,
+                    (AreaId::Foundation(_), AreaId::Tableaux(_)) => -15,
+                    _ => 0,
+                },
+                Move::Reveal { .. } => 5,
+                Move::Restock { .. } => {
+                    restock_count += 1;
+                    self.restock_penalty(restock_count)
+                }
+                Move::Draw { .. } => 0,
+            };
+
+            (points + change).max(0)
+        })
+    }
+
+    /// Vegas scores can go negative: that's money lost on the deal.
+    fn vegas_points(&self, moves: &[Move]) -> i32 {
+        let deal_cost = VEGAS_DEAL_COST * i32::from(self.deck_count);
+
+        moves.iter().fold(-deal_cost, |points, game_move| {
+            let change = match *game_move {
+                Move::Transfer { source, target, .. } => match (source, target) {
+                    (AreaId::Foundation(_), AreaId::Foundation(_)) => 0,
+                    (_, AreaId::Foundation(_)) => VEGAS_FOUNDATION_CARD_PAYOUT,
+                    (AreaId::Foundation(_), _) => -VEGAS_FOUNDATION_CARD_PAYOUT,
+                    _ => 0,
+                },
+                _ => 0,
+            };
+
+            points + change
+        })
+    }
+
+    fn restock_penalty(&self, restock_count: usize) -> i32 {
+        if self.draw_from_stock_len <= 1 {
+            -100
+        } else if restock_count >= FREE_PASSES {
+            // The first restock starts the second pass, so we're past the free passes once we've
+            // restocked as many times as there are free passes.
+            -20
+        } else {
+            0
+        }
+    }
+}
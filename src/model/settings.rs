@@ -1,6 +1,15 @@
 use config::{Config, ConfigError, Environment, File, FileFormat};
 use directories::{ProjectDirs, UserDirs};
 use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use super::keys::{self, KeySettings};
 
 static QUALIFIER: &str = "net";
 static ORGANIZATION: &str = "upflitinglemma";
@@ -12,33 +21,147 @@ static HOME_CONFIG_FILE: &str = ".klondike-rs.toml";
 static ENV_PREFIX: &str = "klondike_";
 static ENV_SEPARATOR: &str = "__";
 
+/// How many tableaux piles a one-deck game can have. Fewer than five and there's little room to
+/// play; ten piles take 55 cards, and the deck runs out before they're dealt.
+pub static TABLEAUX_LEN_RANGE: RangeInclusive<u8> = 5..=9;
+
+/// How many tableaux piles a two-deck game can have. Any fewer and the stock is too deep to play
+/// through.
+pub static DOUBLE_DECK_TABLEAUX_LEN_RANGE: RangeInclusive<u8> = 9..=10;
+
+/// How many decks a game can be dealt from: one for Klondike, or two for Double Klondike.
+pub static DECK_COUNT_RANGE: RangeInclusive<u8> = 1..=2;
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Unable to read settings: {}", source))]
+    ReadError { source: ConfigError },
+
+    #[snafu(display(
+        "Invalid tableaux_len: {}, expected {} to {} for {} deck(s)",
+        len,
+        range.start(),
+        range.end(),
+        deck_count
+    ))]
+    InvalidTableauxLen {
+        len: u8,
+        deck_count: u8,
+        range: RangeInclusive<u8>,
+    },
+
+    #[snafu(display(
+        "Invalid deck_count: {}, expected {} to {}",
+        count,
+        DECK_COUNT_RANGE.start(),
+        DECK_COUNT_RANGE.end()
+    ))]
+    InvalidDeckCount { count: u8 },
+
+    #[snafu(display("Invalid seed: {}, expected at most {}", seed, i64::MAX))]
+    InvalidSeed { seed: u64 },
+
+    #[snafu(display("Invalid key bindings: {}", source))]
+    InvalidKeys { source: keys::Error },
+}
+
+pub type Result<T, E = Error> = ::std::result::Result<T, E>;
+
+/// The standard directories where we keep our files, if they can be found for this system.
+pub fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from(QUALIFIER, ORGANIZATION, APPLICATION)
+}
+
+/// The directory of the settings file in use: wherever the file given on the command line is, or
+/// else the config directory.
+pub fn config_dir(overrides: &SettingsOverrides) -> Option<PathBuf> {
+    match overrides.config_file {
+        Some(ref path) => path.parent().map(Path::to_path_buf),
+        None => project_dirs().map(|project_dirs| project_dirs.config_dir().to_path_buf()),
+    }
+}
+
 #[derive(Default, Debug, Deserialize, Serialize)]
 #[serde(default)]
 pub struct Settings {
     pub display: DisplaySettings,
     pub game: GameSettings,
+    pub keys: KeySettings,
+}
+
+/// Settings given on the command line. They're merged in last, so they win over the settings files
+/// and the environment.
+#[derive(Debug, Default)]
+pub struct SettingsOverrides {
+    /// Read settings from this file instead of from the usual places
+    pub config_file: Option<PathBuf>,
+    pub dealer: Option<DealerMode>,
+    pub draw_from_stock_len: Option<usize>,
 }
 
 impl Settings {
-    // TODO: Return a snafu-defined error type
-    pub fn read_from_system() -> Result<Settings, ConfigError> {
+    pub fn read_from_system(overrides: &SettingsOverrides) -> Result<Settings> {
         let mut config = Config::new();
 
+        if let Some(ref path) = overrides.config_file {
+            config
+                .merge(File::from(path.as_path()).format(FileFormat::Toml))
+                .context(ReadError)?;
+        } else {
+            Self::merge_system_files(&mut config)?;
+        }
+
+        config
+            .merge(Environment::with_prefix(ENV_PREFIX).separator(ENV_SEPARATOR))
+            .context(ReadError)?;
+
+        if let Some(dealer) = overrides.dealer {
+            let value: config::Value = match dealer {
+                DealerMode::Seeded(seed) => {
+                    let seed = i64::try_from(seed).ok().context(InvalidSeed { seed })?;
+                    let mut table = HashMap::new();
+                    table.insert(dealer.name().to_string(), seed);
+                    table.into()
+                }
+                _ => dealer.name().into(),
+            };
+            config.set("game.dealer", value).context(ReadError)?;
+        }
+
+        if let Some(len) = overrides.draw_from_stock_len {
+            // Any count too big for the settings is too big to draw anyway.
+            let len = i64::try_from(len).unwrap_or(i64::MAX);
+            config
+                .set("game.draw_from_stock_len", len)
+                .context(ReadError)?;
+        }
+
+        let settings: Settings = config.try_into().context(ReadError)?;
+        settings.game.validate()?;
+        settings.keys.validate().context(InvalidKeys)?;
+
+        Ok(settings)
+    }
+
+    /// Merge in whichever of the usual settings files exist.
+    fn merge_system_files(config: &mut Config) -> Result<()> {
         if let Some(user_dirs) = UserDirs::new() {
             let mut path = user_dirs.home_dir().to_path_buf();
             path.push(HOME_CONFIG_FILE);
-            config.merge(File::from(path).format(FileFormat::Toml).required(false))?;
+            config
+                .merge(File::from(path).format(FileFormat::Toml).required(false))
+                .context(ReadError)?;
         }
 
-        if let Some(project_dirs) = ProjectDirs::from(QUALIFIER, ORGANIZATION, APPLICATION) {
+        if let Some(project_dirs) = project_dirs() {
             let mut path = project_dirs.config_dir().to_path_buf();
             path.push(CONFIG_FILE);
-            config.merge(File::from(path).format(FileFormat::Toml).required(false))?;
+            config
+                .merge(File::from(path).format(FileFormat::Toml).required(false))
+                .context(ReadError)?;
         }
 
-        config.merge(Environment::with_prefix(ENV_PREFIX).separator(ENV_SEPARATOR))?;
-
-        config.try_into()
+        Ok(())
     }
 }
 
@@ -47,6 +170,8 @@ impl Settings {
 pub struct DisplaySettings {
     pub color: bool,
     pub unicode: bool,
+    /// The name of the theme: a built-in theme, or a theme file in the themes directory
+    pub theme: String,
 }
 
 impl Default for DisplaySettings {
@@ -54,6 +179,7 @@ impl Default for DisplaySettings {
         DisplaySettings {
             color: true,
             unicode: true,
+            theme: "classic".to_string(),
         }
     }
 }
@@ -63,24 +189,125 @@ pub enum DealerMode {
     AutoWin,
     InOrder,
     Random,
+    /// Always deal the shuffle for this seed, e.g., to replay a deal shown in the game.
+    Seeded(u64),
+}
+
+impl DealerMode {
+    /// The name of the mode as written in the settings file, leaving out any seed.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DealerMode::AutoWin => "AutoWin",
+            DealerMode::InOrder => "InOrder",
+            DealerMode::Random => "Random",
+            DealerMode::Seeded(_) => "Seeded",
+        }
+    }
+}
+
+/// Which solitaire game to play. Each variant has its own [rules](super::rules::Rules).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize, Serialize)]
+pub enum Variant {
+    Klondike,
+    /// Like Klondike, but the whole deck is dealt to the tableaux, and any face-up cards can be
+    /// moved together.
+    Yukon,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize, Serialize)]
+pub enum ScoringMode {
+    /// Classic Windows scoring, with points for each move and a time bonus for winning.
+    Standard,
+    /// Each deal costs 52, each card played to a foundation pays 5, and the winnings are kept in
+    /// a bankroll from game to game. Passes through the stock are limited.
+    Vegas,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(default)]
 pub struct GameSettings {
+    pub variant: Variant,
     pub dealer: DealerMode,
+    /// How many decks to deal from. Each deck adds a foundation for every suit.
+    pub deck_count: u8,
     pub draw_from_stock_len: usize,
     pub tableaux_len: u8,
     pub take_from_foundation: bool,
+    /// Whether to move cards to the foundations on their own once they can't be needed in the
+    /// tableaux anymore
+    pub auto_move_to_foundation: bool,
+    pub scoring: ScoringMode,
+    /// How many times the player may go through the stock, counting the first pass. `None` for no
+    /// limit.
+    pub max_stock_passes: Option<usize>,
+    /// Whether to deal every card face up, including the stock, so that deals can be studied. Also
+    /// known as Thoughtful Solitaire.
+    pub open_deck: bool,
+}
+
+impl GameSettings {
+    /// Check the settings that the config format alone can't rule out.
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            DECK_COUNT_RANGE.contains(&self.deck_count),
+            InvalidDeckCount {
+                count: self.deck_count
+            }
+        );
+
+        let range = self.tableaux_len_range();
+        ensure!(
+            range.contains(&self.tableaux_len),
+            InvalidTableauxLen {
+                len: self.tableaux_len,
+                deck_count: self.deck_count,
+                range,
+            }
+        );
+
+        Ok(())
+    }
+
+    fn tableaux_len_range(&self) -> RangeInclusive<u8> {
+        if self.deck_count > 1 {
+            DOUBLE_DECK_TABLEAUX_LEN_RANGE.clone()
+        } else {
+            TABLEAUX_LEN_RANGE.clone()
+        }
+    }
+
+    /// How many times the player may go through the stock, if it's limited, taking the scoring
+    /// rules into account. Vegas allows one pass when drawing one card at a time, and three
+    /// otherwise. The stricter of that and `max_stock_passes` applies.
+    pub fn stock_pass_limit(&self) -> Option<usize> {
+        let scoring_limit = match self.scoring {
+            ScoringMode::Standard => None,
+            ScoringMode::Vegas if self.draw_from_stock_len <= 1 => Some(1),
+            ScoringMode::Vegas => Some(3),
+        };
+
+        match (scoring_limit, self.max_stock_passes) {
+            (Some(scoring_limit), Some(max_stock_passes)) => {
+                Some(scoring_limit.min(max_stock_passes))
+            }
+            (scoring_limit, max_stock_passes) => scoring_limit.or(max_stock_passes),
+        }
+    }
 }
 
 impl Default for GameSettings {
     fn default() -> Self {
         GameSettings {
+            variant: Variant::Klondike,
             dealer: DealerMode::Random,
+            deck_count: 1,
             draw_from_stock_len: 3,
             tableaux_len: 7,
             take_from_foundation: true,
+            auto_move_to_foundation: false,
+            scoring: ScoringMode::Standard,
+            max_stock_passes: None,
+            open_deck: false,
         }
     }
 }
@@ -0,0 +1,585 @@
+//! Module for searching for a winning sequence of moves from a Klondike position.
+//!
+//! The search doesn't work on the [areas](super::area::Area) directly. Boxed areas behind the
+//! [area list](super::area_list::AreaList) zipper are far too slow to clone and compare by the
+//! hundred thousand, so we first translate the game into a compact [position](Position) and search
+//! over that instead.
+
+use snafu::ResultExt;
+use std::{
+    collections::HashSet,
+    convert::TryFrom,
+    time::{Duration, Instant},
+};
+
+use super::{
+    area::AreaId,
+    area_list,
+    card::{Card, Color, Rank, Suit},
+    game::Game,
+    settings::{GameSettings, Variant},
+};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Unable to read game position: {}", source))]
+    PositionError { source: area_list::Error },
+
+    #[snafu(display("Unable to solve {:?} games, only Klondike", variant))]
+    UnsupportedVariant { variant: Variant },
+}
+
+pub type Result<T, E = Error> = ::std::result::Result<T, E>;
+
+const SUIT_LEN: usize = 4;
+const RANK_LEN: u8 = 13;
+
+/// How much work the solver may do before giving up with [`Solution::Unknown`].
+#[derive(Copy, Clone, Debug)]
+pub struct Limits {
+    /// The maximum number of distinct positions to explore
+    pub max_nodes: usize,
+    /// The maximum time to spend searching
+    pub max_duration: Duration,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_nodes: 250_000,
+            max_duration: Duration::from_secs(2),
+        }
+    }
+}
+
+/// A single step of a solution, in terms of the game's areas.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Move {
+    /// Draw cards from the stock onto the talon.
+    Draw,
+    /// Flip the talon back over onto the stock.
+    Restock,
+    /// Move the top `len` cards of the source area onto the target area. Any face-down card left
+    /// on top of a source tableaux is turned face up as part of the move.
+    Transfer {
+        source: AreaId,
+        target: AreaId,
+        len: usize,
+    },
+}
+
+/// The result of searching a position.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Solution {
+    /// The position can be won by making these moves in order.
+    Solvable(Vec<Move>),
+    /// Every line of play was explored, and none of them wins.
+    Unsolvable,
+    /// The search ran out of budget before finding an answer.
+    Unknown,
+}
+
+/// A compact copy of a Klondike position. Cards are packed into a byte each, and only the state
+/// that matters to the rules is kept, so positions are cheap to clone, hash and compare.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Position {
+    /// The stock, with the top card last
+    stock: Vec<u8>,
+    /// The talon, with the top card last
+    talon: Vec<u8>,
+    /// How many more passes through the stock are allowed, if they're limited
+    passes_remaining: Option<usize>,
+    /// The number of cards on each foundation, in the same order as the foundation area ids
+    foundations: Vec<u8>,
+    tableaux: Vec<Column>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct Column {
+    /// The cards in the column, with the top card last
+    cards: Vec<u8>,
+    /// How many cards at the bottom of the column are still face down
+    face_down_len: usize,
+}
+
+fn pack(card: &Card) -> u8 {
+    u8::from(card.suit) * RANK_LEN + u8::from(card.rank) - 1
+}
+
+fn suit_index(card: u8) -> usize {
+    usize::from(card / RANK_LEN)
+}
+
+fn rank(card: u8) -> u8 {
+    card % RANK_LEN + 1
+}
+
+fn color(card: u8) -> Color {
+    Suit::try_from(card / RANK_LEN).unwrap().color()
+}
+
+fn is_king(card: u8) -> bool {
+    rank(card) == u8::from(Rank::King)
+}
+
+/// Whether `card` can be placed on `base` in a tableaux.
+fn builds_on(card: u8, base: u8) -> bool {
+    rank(card) + 1 == rank(base) && color(card) != color(base)
+}
+
+impl Column {
+    fn face_up_len(&self) -> usize {
+        self.cards.len() - self.face_down_len
+    }
+
+    /// The length of the movable run of alternating colors and descending ranks at the top.
+    fn run_len(&self) -> usize {
+        let face_up = &self.cards[self.face_down_len..];
+
+        if face_up.is_empty() {
+            return 0;
+        }
+
+        1 + face_up
+            .windows(2)
+            .rev()
+            .take_while(|pair| builds_on(pair[1], pair[0]))
+            .count()
+    }
+
+    fn accepts(&self, card: u8) -> bool {
+        match self.cards.last() {
+            Some(&top) => builds_on(card, top),
+            None => is_king(card),
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Vec<u8> {
+        let cards = self.cards.split_off(self.cards.len() - len);
+
+        // Turn over the next card if we've uncovered it.
+        if self.face_down_len > 0 && self.face_down_len == self.cards.len() {
+            self.face_down_len -= 1;
+        }
+
+        cards
+    }
+}
+
+impl Position {
+    /// Read the position of a game in progress. Any held cards are treated as if they had been
+    /// returned to where they were picked up.
+    pub fn from_game(game: &Game) -> Result<Position> {
+        let mut snapshot = game.areas.snapshot();
+        snapshot.return_held().context(PositionError)?;
+
+        let passes_remaining = game
+            .areas
+            .get_by_area_id(AreaId::Stock)
+            .context(PositionError)?
+            .passes_remaining();
+
+        let mut position = Position {
+            stock: vec![],
+            talon: vec![],
+            passes_remaining,
+            foundations: vec![],
+            tableaux: vec![],
+        };
+
+        for area in snapshot.areas {
+            let cards = area.cards.iter().map(pack).collect::<Vec<_>>();
+
+            match area.id {
+                AreaId::Stock => position.stock = cards,
+                AreaId::Talon => position.talon = cards,
+                AreaId::Foundation(index) => {
+                    let index = usize::from(index);
+
+                    if position.foundations.len() <= index {
+                        position.foundations.resize(index + 1, 0);
+                    }
+
+                    position.foundations[index] = u8::try_from(cards.len()).unwrap_or(RANK_LEN);
+                }
+                AreaId::Tableaux(_) => {
+                    // We don't bother with the step of turning over a face-down card. The solver
+                    // always turns them over right away.
+                    let face_down_len = cards.len().saturating_sub(area.spread_len.max(1));
+                    position.tableaux.push(Column {
+                        cards,
+                        face_down_len,
+                    });
+                }
+            }
+        }
+
+        Ok(position)
+    }
+
+    pub fn is_win(&self) -> bool {
+        self.foundations.iter().all(|&len| len == RANK_LEN)
+    }
+
+    /// A copy of the position with the tableaux sorted, so that positions that differ only in the
+    /// order of the tableaux compare equal. Swapping two columns doesn't change whether a position
+    /// can be won. We keep the whole position rather than a hash of it, since a hash collision
+    /// would make us skip a position we never actually explored.
+    fn transposition_key(&self) -> Position {
+        let mut key = self.clone();
+        key.tableaux.sort();
+        key
+    }
+
+    /// The first foundation that would take the card, if any. With more than one deck, there's
+    /// more than one foundation for each suit.
+    fn foundation_for(&self, card: u8) -> Option<usize> {
+        self.foundations
+            .iter()
+            .enumerate()
+            .position(|(index, &len)| index % SUIT_LEN == suit_index(card) && len + 1 == rank(card))
+    }
+
+    fn accepts_on_foundation(&self, card: u8) -> bool {
+        self.foundation_for(card).is_some()
+    }
+
+    fn foundation_id(&self, card: u8) -> AreaId {
+        foundation_id(self.foundation_for(card).unwrap())
+    }
+
+    /// Whether a card can go to a foundation without any chance of being needed in the tableaux
+    /// later. That's true once every foundation of the other color has the cards that could be
+    /// built on it.
+    fn is_safe_for_foundation(&self, card: u8) -> bool {
+        let card_rank = rank(card);
+        let card_color = color(card);
+
+        self.accepts_on_foundation(card)
+            && (card_rank <= 2
+                || self.foundations.iter().enumerate().all(|(index, &len)| {
+                    let suit = Suit::try_from((index % SUIT_LEN) as u8).unwrap();
+                    suit.color() == card_color || len + 1 >= card_rank
+                }))
+    }
+
+    /// Play every tableaux card that can safely go to a foundation, recording the moves.
+    fn apply_safe_moves(&mut self, path: &mut Vec<Move>) {
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            for index in 0..self.tableaux.len() {
+                let top = self.tableaux[index].cards.last().copied();
+
+                if let Some(card) = top.filter(|&card| self.is_safe_for_foundation(card)) {
+                    let mv = Move::Transfer {
+                        source: tableaux_id(index),
+                        target: self.foundation_id(card),
+                        len: 1,
+                    };
+                    self.apply(mv, 0);
+                    path.push(mv);
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    fn apply(&mut self, mv: Move, draw_from_stock_len: usize) {
+        match mv {
+            Move::Draw => {
+                let len = draw_from_stock_len.min(self.stock.len());
+                let drawn = self.stock.split_off(self.stock.len() - len);
+                self.talon.extend(drawn.into_iter().rev());
+            }
+            Move::Restock => {
+                self.stock = self.talon.drain(..).rev().collect();
+                self.passes_remaining = self
+                    .passes_remaining
+                    .map(|passes_remaining| passes_remaining.saturating_sub(1));
+            }
+            Move::Transfer {
+                source,
+                target,
+                len,
+            } => {
+                let cards = match source {
+                    AreaId::Talon => self.talon.split_off(self.talon.len() - len),
+                    AreaId::Foundation(index) => {
+                        let index = usize::from(index);
+                        self.foundations[index] -= 1;
+                        vec![foundation_card(index, self.foundations[index])]
+                    }
+                    AreaId::Tableaux(index) => self.tableaux[usize::from(index)].take(len),
+                    AreaId::Stock => unreachable!("Cards are never moved out of the stock"),
+                };
+
+                match target {
+                    AreaId::Foundation(index) => {
+                        self.foundations[usize::from(index)] += 1;
+                    }
+                    AreaId::Tableaux(index) => {
+                        self.tableaux[usize::from(index)].cards.extend(cards);
+                    }
+                    AreaId::Stock | AreaId::Talon => {
+                        unreachable!("Cards are never moved onto the stock or talon")
+                    }
+                }
+            }
+        }
+    }
+
+    /// List the legal moves from this position, most promising first. Moves that can't change
+    /// whether the game is won, like shuffling a king between empty columns, are left out.
+    fn moves(&self, take_from_foundation: bool) -> Vec<Move> {
+        let mut to_foundation = vec![];
+        let mut revealing = vec![];
+        let mut from_talon = vec![];
+        let mut other = vec![];
+        let mut splitting = vec![];
+        let mut from_foundation = vec![];
+
+        let first_empty = self
+            .tableaux
+            .iter()
+            .position(|column| column.cards.is_empty());
+
+        // Only offer one empty column as a target, since they're all alike.
+        let targets = |card: u8| {
+            self.tableaux
+                .iter()
+                .enumerate()
+                .filter(move |&(index, column)| {
+                    if column.cards.is_empty() {
+                        Some(index) == first_empty && is_king(card)
+                    } else {
+                        column.accepts(card)
+                    }
+                })
+                .map(|(index, _)| index)
+        };
+
+        for (source_index, column) in self.tableaux.iter().enumerate() {
+            let source = tableaux_id(source_index);
+
+            if let Some(&top) = column.cards.last() {
+                if self.accepts_on_foundation(top) {
+                    to_foundation.push(Move::Transfer {
+                        source,
+                        target: self.foundation_id(top),
+                        len: 1,
+                    });
+                }
+            }
+
+            let run_len = column.run_len();
+
+            for len in 1..=run_len {
+                let card = column.cards[column.cards.len() - len];
+
+                // Moving a whole column onto an empty column gets us nowhere.
+                if len == column.cards.len() && is_king(card) {
+                    continue;
+                }
+
+                for target_index in targets(card) {
+                    if target_index == source_index {
+                        continue;
+                    }
+
+                    let mv = Move::Transfer {
+                        source,
+                        target: tableaux_id(target_index),
+                        len,
+                    };
+
+                    if len == column.face_up_len() && column.face_down_len > 0 {
+                        revealing.push((column.face_down_len, mv));
+                    } else if len < column.face_up_len()
+                        && !self.accepts_on_foundation(column.cards[column.cards.len() - len - 1])
+                    {
+                        splitting.push(mv);
+                    } else {
+                        other.push(mv);
+                    }
+                }
+            }
+        }
+
+        if let Some(&top) = self.talon.last() {
+            if self.accepts_on_foundation(top) {
+                to_foundation.push(Move::Transfer {
+                    source: AreaId::Talon,
+                    target: self.foundation_id(top),
+                    len: 1,
+                });
+            }
+
+            for target_index in targets(top) {
+                from_talon.push(Move::Transfer {
+                    source: AreaId::Talon,
+                    target: tableaux_id(target_index),
+                    len: 1,
+                });
+            }
+        }
+
+        if take_from_foundation {
+            for (index, &len) in self.foundations.iter().enumerate() {
+                if len > 0 {
+                    let card = foundation_card(index, len - 1);
+
+                    for target_index in targets(card) {
+                        from_foundation.push(Move::Transfer {
+                            source: foundation_id(index),
+                            target: tableaux_id(target_index),
+                            len: 1,
+                        });
+                    }
+                }
+            }
+        }
+
+        // Uncover the deepest piles first, since they hide the most cards.
+        revealing.sort_by(|(left, _), (right, _)| right.cmp(left));
+
+        let mut moves = to_foundation;
+        moves.extend(revealing.into_iter().map(|(_, mv)| mv));
+        moves.extend(from_talon);
+
+        if !self.stock.is_empty() {
+            moves.push(Move::Draw);
+        } else if !self.talon.is_empty() && self.passes_remaining != Some(0) {
+            moves.push(Move::Restock);
+        }
+
+        moves.extend(other);
+        moves.extend(from_foundation);
+        moves.extend(splitting);
+        moves
+    }
+}
+
+fn tableaux_id(index: usize) -> AreaId {
+    AreaId::Tableaux(u8::try_from(index).unwrap())
+}
+
+fn foundation_id(index: usize) -> AreaId {
+    AreaId::Foundation(u8::try_from(index).unwrap())
+}
+
+/// The card at `position` in the foundation at `index`, counting from the ace at 0.
+fn foundation_card(index: usize, position: u8) -> u8 {
+    u8::try_from(index % SUIT_LEN).unwrap() * RANK_LEN + position
+}
+
+/// Searches for a winning line of play, following the same settings as the game.
+#[derive(Clone, Debug)]
+pub struct Solver {
+    variant: Variant,
+    draw_from_stock_len: usize,
+    take_from_foundation: bool,
+    limits: Limits,
+}
+
+struct Frame {
+    position: Position,
+    moves: Vec<Move>,
+    next_move: usize,
+    path_len: usize,
+}
+
+impl Solver {
+    pub fn new(settings: &GameSettings) -> Solver {
+        Solver {
+            variant: settings.variant,
+            draw_from_stock_len: settings.draw_from_stock_len.max(1),
+            take_from_foundation: settings.take_from_foundation,
+            limits: Limits::default(),
+        }
+    }
+
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn solve_game(&self, game: &Game) -> Result<Solution> {
+        ensure!(
+            self.variant == Variant::Klondike,
+            UnsupportedVariant {
+                variant: self.variant
+            }
+        );
+
+        let position = Position::from_game(game)?;
+        Ok(self.solve(&position))
+    }
+
+    /// Run a depth-first search from the position. We never visit the same position twice (up to
+    /// reordering the tableaux), so if we run out of positions without winning, there is no win.
+    pub fn solve(&self, position: &Position) -> Solution {
+        let deadline = Instant::now() + self.limits.max_duration;
+
+        let mut path = vec![];
+        let mut root = position.clone();
+        root.apply_safe_moves(&mut path);
+
+        if root.is_win() {
+            return Solution::Solvable(path);
+        }
+
+        let mut visited = HashSet::new();
+        visited.insert(root.transposition_key());
+
+        let mut stack = vec![Frame {
+            moves: root.moves(self.take_from_foundation),
+            position: root,
+            next_move: 0,
+            path_len: path.len(),
+        }];
+
+        while let Some(frame) = stack.last_mut() {
+            let mv = if let Some(&mv) = frame.moves.get(frame.next_move) {
+                frame.next_move += 1;
+                mv
+            } else {
+                stack.pop();
+                continue;
+            };
+
+            path.truncate(frame.path_len);
+            path.push(mv);
+
+            let mut child = frame.position.clone();
+            child.apply(mv, self.draw_from_stock_len);
+            child.apply_safe_moves(&mut path);
+
+            if child.is_win() {
+                return Solution::Solvable(path);
+            }
+
+            if !visited.insert(child.transposition_key()) {
+                continue;
+            }
+
+            // Checking the clock is comparatively slow, so only do it every so often.
+            if visited.len() >= self.limits.max_nodes
+                || (visited.len() % 1024 == 0 && Instant::now() >= deadline)
+            {
+                return Solution::Unknown;
+            }
+
+            stack.push(Frame {
+                moves: child.moves(self.take_from_foundation),
+                position: child,
+                next_move: 0,
+                path_len: path.len(),
+            });
+        }
+
+        Solution::Unsolvable
+    }
+}
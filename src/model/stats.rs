@@ -0,0 +1,337 @@
+//! Module for keeping statistics on every game the player finishes or gives up on.
+//!
+//! Each game is recorded as one line of JSON in the statistics file, so recording a game only ever
+//! appends to the file, and a damaged line only loses that one game.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt};
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    time::Duration,
+};
+
+use super::{
+    area::AreaId,
+    game::Game,
+    settings::{project_dirs, DealerMode, GameSettings},
+};
+
+static STATS_FILE: &str = "statistics.jsonl";
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Unable to find a data directory for statistics"))]
+    NoDataDirectory,
+
+    #[snafu(display("Unable to access statistics file {}: {}", path.display(), source))]
+    FileError { path: PathBuf, source: io::Error },
+
+    #[snafu(display("Unable to write statistics file {}: {}", path.display(), source))]
+    FormatError {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+
+    #[snafu(display("Unable to export statistics as CSV: {}", source))]
+    CsvExportError { source: csv::Error },
+
+    #[snafu(display("Unable to export statistics as JSON: {}", source))]
+    JsonExportError { source: serde_json::Error },
+}
+
+pub type Result<T, E = Error> = ::std::result::Result<T, E>;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum GameResult {
+    Won,
+    /// The game was given up on, e.g., by dealing a new one before winning.
+    Lost,
+}
+
+/// How one game went.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GameRecord {
+    /// When the game ended
+    pub date: DateTime<Utc>,
+    pub seed: Option<u64>,
+    pub dealer: DealerMode,
+    pub draw_from_stock_len: usize,
+    pub result: GameResult,
+    /// How many of the player's actions did anything, including just moving the selection, as
+    /// counted by [`Game::move_count`](crate::model::Game::move_count)
+    pub moves: usize,
+    /// How long the game was played
+    pub time: Duration,
+    pub score: i32,
+    /// How many cards made it to the foundations
+    pub foundation_len: usize,
+}
+
+impl GameRecord {
+    /// Record how the game went, as of now.
+    pub fn of(game: &Game, settings: &GameSettings) -> GameRecord {
+        let foundation_len = game
+            .areas
+            .iter()
+            .filter(|area| matches!(area.id(), AreaId::Foundation(_)))
+            .map(|area| area.as_stack().cards.len())
+            .sum();
+
+        GameRecord {
+            date: Utc::now(),
+            seed: game.seed,
+            dealer: settings.dealer,
+            draw_from_stock_len: settings.draw_from_stock_len,
+            result: if game.is_win() {
+                GameResult::Won
+            } else {
+                GameResult::Lost
+            },
+            moves: game.move_count,
+            time: game.elapsed(),
+            score: game.score.total(),
+            foundation_len,
+        }
+    }
+
+    pub fn is_win(&self) -> bool {
+        self.result == GameResult::Won
+    }
+}
+
+/// Every game recorded so far, oldest first.
+#[derive(Clone, Debug, Default)]
+pub struct Statistics {
+    pub records: Vec<GameRecord>,
+}
+
+impl Statistics {
+    pub fn games_len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn wins_len(&self) -> usize {
+        self.records.iter().filter(|record| record.is_win()).count()
+    }
+
+    /// The share of games won, from 0 to 1, or `None` if no games have been played.
+    pub fn win_rate(&self) -> Option<f64> {
+        if self.records.is_empty() {
+            None
+        } else {
+            Some(self.wins_len() as f64 / self.games_len() as f64)
+        }
+    }
+
+    /// How many of the most recent games were won in a row.
+    pub fn current_streak(&self) -> usize {
+        self.records
+            .iter()
+            .rev()
+            .take_while(|record| record.is_win())
+            .count()
+    }
+
+    /// The most games ever won in a row.
+    pub fn best_streak(&self) -> usize {
+        self.records
+            .split(|record| !record.is_win())
+            .map(<[GameRecord]>::len)
+            .max()
+            .unwrap_or_default()
+    }
+
+    pub fn fastest_win(&self) -> Option<Duration> {
+        self.records
+            .iter()
+            .filter(|record| record.is_win())
+            .map(|record| record.time)
+            .min()
+    }
+
+    /// How many games ended with each number of cards on the foundations, in buckets of
+    /// `bucket_len` cards. Each bucket is returned with the fewest cards that fall into it.
+    pub fn foundation_histogram(&self, bucket_len: usize) -> Vec<(usize, usize)> {
+        let bucket_len = bucket_len.max(1);
+        let max_len = self
+            .records
+            .iter()
+            .map(|record| record.foundation_len)
+            .max()
+            .unwrap_or_default();
+
+        let mut buckets = vec![0; max_len / bucket_len + 1];
+        for record in &self.records {
+            buckets[record.foundation_len / bucket_len] += 1;
+        }
+
+        buckets
+            .into_iter()
+            .enumerate()
+            .map(|(index, count)| (index * bucket_len, count))
+            .collect()
+    }
+
+    pub fn record(&mut self, record: GameRecord) {
+        self.records.push(record);
+    }
+}
+
+/// Which recorded games to export. Every filter that's set has to match.
+#[derive(Clone, Debug, Default)]
+pub struct RecordFilter {
+    /// The first day to include, in UTC
+    pub since: Option<NaiveDate>,
+    /// The last day to include, in UTC
+    pub until: Option<NaiveDate>,
+    /// The [name](DealerMode::name) of the dealer mode to include, ignoring case
+    pub dealer: Option<String>,
+}
+
+impl RecordFilter {
+    pub fn matches(&self, record: &GameRecord) -> bool {
+        let date = record.date.naive_utc().date();
+
+        self.since.map_or(true, |since| since <= date)
+            && self.until.map_or(true, |until| date <= until)
+            && self.dealer.as_ref().map_or(true, |dealer| {
+                dealer.eq_ignore_ascii_case(record.dealer.name())
+            })
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// One recorded game, flattened for spreadsheets and notebooks.
+#[derive(Debug, Serialize)]
+struct ExportRecord {
+    date: DateTime<Utc>,
+    seed: Option<u64>,
+    dealer: &'static str,
+    draw_count: usize,
+    result: GameResult,
+    moves: usize,
+    /// How long the game was played, in seconds
+    time: u64,
+    score: i32,
+    foundation_len: usize,
+}
+
+impl From<&GameRecord> for ExportRecord {
+    fn from(record: &GameRecord) -> Self {
+        ExportRecord {
+            date: record.date,
+            seed: record.seed,
+            dealer: record.dealer.name(),
+            draw_count: record.draw_from_stock_len,
+            result: record.result,
+            moves: record.moves,
+            time: record.time.as_secs(),
+            score: record.score,
+            foundation_len: record.foundation_len,
+        }
+    }
+}
+
+/// Write the recorded games that match the filter, oldest first. CSV gets a header row and one
+/// row per game, and JSON gets an array with one object per game.
+pub fn export_records<W>(
+    statistics: &Statistics,
+    filter: &RecordFilter,
+    format: ExportFormat,
+    writer: W,
+) -> Result<()>
+where
+    W: Write,
+{
+    let records = statistics
+        .records
+        .iter()
+        .filter(|record| filter.matches(record))
+        .map(ExportRecord::from);
+
+    match format {
+        ExportFormat::Csv => {
+            let mut csv_writer = csv::Writer::from_writer(writer);
+            for record in records {
+                csv_writer.serialize(record).context(CsvExportError)?;
+            }
+            csv_writer
+                .flush()
+                .map_err(csv::Error::from)
+                .context(CsvExportError)?;
+        }
+        ExportFormat::Json => {
+            let records: Vec<_> = records.collect();
+            serde_json::to_writer_pretty(writer, &records).context(JsonExportError)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn stats_file_path() -> Result<PathBuf> {
+    let project_dirs = project_dirs().context(NoDataDirectory)?;
+
+    let mut path = project_dirs.data_dir().to_path_buf();
+    path.push(STATS_FILE);
+
+    Ok(path)
+}
+
+/// Load every recorded game. A player who has never finished a game has no statistics yet. Lines
+/// that can't be read are skipped, so one bad record doesn't cost the rest.
+pub fn load_statistics() -> Result<Statistics> {
+    let path = stats_file_path()?;
+
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => {
+            return Ok(Statistics::default())
+        }
+        Err(error) => return Err(error).context(FileError { path }),
+    };
+
+    let records = contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            serde_json::from_str(line)
+                .map_err(|error| warn!("Skipping unreadable game record: {}", error))
+                .ok()
+        })
+        .collect();
+
+    Ok(Statistics { records })
+}
+
+/// Add a game to the end of the statistics file.
+pub fn append_record(record: &GameRecord) -> Result<()> {
+    let path = stats_file_path()?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context(FileError { path: parent })?;
+    }
+
+    let mut line = serde_json::to_string(record).context(FormatError { path: &path })?;
+    line.push('\n');
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .context(FileError { path: &path })?;
+    file.write_all(line.as_bytes())
+        .context(FileError { path: &path })?;
+
+    info!("Recorded game in {}", path.display());
+    Ok(())
+}
@@ -4,12 +4,18 @@ use snafu::ResultExt;
 use std::{
     fmt, fs,
     io::{self, Write as _},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
 };
 
 use termion::{
     self, cursor,
+    event::Key,
+    input::TermRead,
     raw::{IntoRawMode, RawTerminal},
     screen::AlternateScreen,
+    style,
 };
 
 #[derive(Debug, Snafu)]
@@ -51,10 +57,12 @@ impl fmt::Debug for TtyInput {
 
 pub struct TtyOutput {
     tty: AlternateScreen<RawTerminal<fs::File>>,
+    /// Whether colours are drawn, and so have to be reset on the way out
+    color: bool,
 }
 
 impl TtyOutput {
-    pub fn new() -> Result<Self> {
+    pub fn new(color: bool) -> Result<Self> {
         let mut tty = AlternateScreen::from(
             termion::get_tty()
                 .context(TtyError)?
@@ -63,7 +71,7 @@ impl TtyOutput {
         );
 
         write!(tty, "{}", cursor::Hide).context(WriteError)?;
-        Ok(TtyOutput { tty })
+        Ok(TtyOutput { tty, color })
     }
 }
 
@@ -79,6 +87,11 @@ impl io::Write for TtyOutput {
 
 impl Drop for TtyOutput {
     fn drop(&mut self) {
+        // Don't leave the theme's colours behind for whatever runs next in the terminal. Without
+        // colour, nothing was drawn that needs resetting, so no style escapes are written at all.
+        if self.color {
+            write!(self.tty, "{}", style::Reset).unwrap();
+        }
         write!(self.tty, "{}", cursor::Show).unwrap();
     }
 }
@@ -88,3 +101,40 @@ impl fmt::Debug for TtyOutput {
         fmt.debug_struct("TtyOutput").field("tty", &"...").finish()
     }
 }
+
+/// Keys read from the TTY on a separate thread, so that waiting for the next one can give up after
+/// a while. Each item is the next key, or `None` if no key was pressed in time.
+#[derive(Debug)]
+pub struct TimedKeys {
+    keys: mpsc::Receiver<io::Result<Key>>,
+    timeout: Duration,
+}
+
+impl TimedKeys {
+    pub fn new(input: TtyInput, timeout: Duration) -> Self {
+        let (sender, keys) = mpsc::channel();
+
+        thread::spawn(move || {
+            for key in input.keys() {
+                // Stop reading once nobody is listening for keys anymore.
+                if sender.send(key).is_err() {
+                    break;
+                }
+            }
+        });
+
+        TimedKeys { keys, timeout }
+    }
+}
+
+impl Iterator for TimedKeys {
+    type Item = io::Result<Option<Key>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.keys.recv_timeout(self.timeout) {
+            Ok(key) => Some(key.map(Some)),
+            Err(RecvTimeoutError::Timeout) => Some(Ok(None)),
+            Err(RecvTimeoutError::Disconnected) => None,
+        }
+    }
+}
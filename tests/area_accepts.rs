@@ -0,0 +1,303 @@
+//! Which cards each kind of area accepts, and which it rejects.
+
+use klondike_lib::model::{
+    area::{
+        foundation::UnselectedFoundation, stock::UnselectedStock, tableaux::UnselectedTableaux,
+        talon::UnselectedTalon, Error, Held, Rejection, UnselectedArea,
+    },
+    settings::{GameSettings, Variant},
+    AreaId, Card, Rank, Suit,
+};
+
+fn card(rank: Rank, suit: Suit) -> Card {
+    Card { rank, suit }
+}
+
+fn held(source: AreaId, cards: Vec<Card>) -> Held {
+    Held { source, cards }
+}
+
+fn tableaux(cards: Vec<Card>, revealed_len: usize) -> Box<dyn UnselectedArea> {
+    UnselectedTableaux::create(0, revealed_len, cards, &GameSettings::default())
+}
+
+fn yukon_tableaux(cards: Vec<Card>, revealed_len: usize) -> Box<dyn UnselectedArea> {
+    let settings = GameSettings {
+        variant: Variant::Yukon,
+        ..GameSettings::default()
+    };
+    UnselectedTableaux::create(0, revealed_len, cards, &settings)
+}
+
+fn foundation(cards: Vec<Card>) -> Box<dyn UnselectedArea> {
+    UnselectedFoundation::create(0, Suit::Hearts, cards, &GameSettings::default())
+}
+
+fn is_invalid_card(error: Error) -> bool {
+    matches!(error, Error::InvalidCard { .. })
+}
+
+/// Whether the held cards were turned down because `expected_card` can't go on `expected_on`.
+fn is_out_of_sequence(error: Error, expected_card: Card, expected_on: Card) -> bool {
+    matches!(
+        error,
+        Error::InvalidCard {
+            rejection: Rejection::OutOfSequence { card, on },
+        } if card == expected_card && on == expected_on
+    )
+}
+
+const OTHER_TABLEAUX: AreaId = AreaId::Tableaux(1);
+
+#[test]
+fn tableaux_accepts_next_rank_of_other_color() {
+    let area = tableaux(vec![card(Rank::Nine, Suit::Spades)], 1);
+
+    for &suit in &[Suit::Hearts, Suit::Diamonds] {
+        let held = held(OTHER_TABLEAUX, vec![card(Rank::Eight, suit)]);
+        assert!(area.accepts_cards(&held).is_ok());
+    }
+}
+
+#[test]
+fn tableaux_rejects_same_color() {
+    let area = tableaux(vec![card(Rank::Nine, Suit::Spades)], 1);
+    let held = held(OTHER_TABLEAUX, vec![card(Rank::Eight, Suit::Clubs)]);
+
+    assert!(is_invalid_card(area.accepts_cards(&held).unwrap_err()));
+}
+
+#[test]
+fn tableaux_rejects_wrong_rank() {
+    let area = tableaux(vec![card(Rank::Nine, Suit::Spades)], 1);
+
+    for &rank in &[Rank::Seven, Rank::Nine, Rank::Ten] {
+        let held = held(OTHER_TABLEAUX, vec![card(rank, Suit::Hearts)]);
+        assert!(is_invalid_card(area.accepts_cards(&held).unwrap_err()));
+    }
+}
+
+#[test]
+fn tableaux_rejects_cards_on_face_down_card() {
+    let area = tableaux(vec![card(Rank::Nine, Suit::Spades)], 0);
+    let held = held(OTHER_TABLEAUX, vec![card(Rank::Eight, Suit::Hearts)]);
+
+    assert!(is_invalid_card(area.accepts_cards(&held).unwrap_err()));
+}
+
+#[test]
+fn empty_tableaux_accepts_only_king() {
+    let area = tableaux(vec![], 0);
+
+    let king = held(OTHER_TABLEAUX, vec![card(Rank::King, Suit::Clubs)]);
+    assert!(area.accepts_cards(&king).is_ok());
+
+    let queen = held(OTHER_TABLEAUX, vec![card(Rank::Queen, Suit::Clubs)]);
+    assert!(is_invalid_card(area.accepts_cards(&queen).unwrap_err()));
+}
+
+#[test]
+fn tableaux_accepts_run() {
+    let area = tableaux(vec![card(Rank::Nine, Suit::Spades)], 1);
+    let held = held(
+        OTHER_TABLEAUX,
+        vec![
+            card(Rank::Eight, Suit::Hearts),
+            card(Rank::Seven, Suit::Clubs),
+            card(Rank::Six, Suit::Diamonds),
+        ],
+    );
+
+    assert!(area.accepts_cards(&held).is_ok());
+}
+
+#[test]
+fn tableaux_rejects_run_of_same_color() {
+    let area = tableaux(vec![card(Rank::Nine, Suit::Spades)], 1);
+    let held = held(
+        OTHER_TABLEAUX,
+        vec![
+            card(Rank::Eight, Suit::Hearts),
+            card(Rank::Seven, Suit::Diamonds),
+        ],
+    );
+
+    let error = area.accepts_cards(&held).unwrap_err();
+    assert!(is_out_of_sequence(
+        error,
+        card(Rank::Seven, Suit::Diamonds),
+        card(Rank::Eight, Suit::Hearts)
+    ));
+}
+
+#[test]
+fn tableaux_rejects_run_out_of_order() {
+    let area = tableaux(vec![], 0);
+    let held = held(
+        OTHER_TABLEAUX,
+        vec![
+            card(Rank::King, Suit::Spades),
+            card(Rank::Queen, Suit::Hearts),
+            card(Rank::Ten, Suit::Clubs),
+        ],
+    );
+
+    let error = area.accepts_cards(&held).unwrap_err();
+    assert!(is_out_of_sequence(
+        error,
+        card(Rank::Ten, Suit::Clubs),
+        card(Rank::Queen, Suit::Hearts)
+    ));
+}
+
+#[test]
+fn tableaux_takes_back_own_cards() {
+    let area = tableaux(vec![card(Rank::Nine, Suit::Spades)], 1);
+    let held = held(
+        AreaId::Tableaux(0),
+        vec![
+            card(Rank::Four, Suit::Spades),
+            card(Rank::Two, Suit::Spades),
+        ],
+    );
+
+    assert!(area.accepts_cards(&held).is_ok());
+}
+
+#[test]
+fn yukon_tableaux_accepts_run_out_of_order() {
+    let area = yukon_tableaux(vec![card(Rank::Nine, Suit::Spades)], 1);
+    let held = held(
+        OTHER_TABLEAUX,
+        vec![
+            card(Rank::Eight, Suit::Hearts),
+            card(Rank::Two, Suit::Hearts),
+        ],
+    );
+
+    assert!(area.accepts_cards(&held).is_ok());
+}
+
+#[test]
+fn yukon_tableaux_rejects_wrong_base() {
+    let area = yukon_tableaux(vec![card(Rank::Nine, Suit::Spades)], 1);
+    let held = held(
+        OTHER_TABLEAUX,
+        vec![
+            card(Rank::Eight, Suit::Clubs),
+            card(Rank::Seven, Suit::Hearts),
+        ],
+    );
+
+    assert!(is_invalid_card(area.accepts_cards(&held).unwrap_err()));
+}
+
+#[test]
+fn empty_foundation_accepts_only_ace_of_its_suit() {
+    let area = foundation(vec![]);
+
+    let ace = held(OTHER_TABLEAUX, vec![card(Rank::Ace, Suit::Hearts)]);
+    assert!(area.accepts_cards(&ace).is_ok());
+
+    let two = held(OTHER_TABLEAUX, vec![card(Rank::Two, Suit::Hearts)]);
+    assert!(is_invalid_card(area.accepts_cards(&two).unwrap_err()));
+
+    let other_ace = held(OTHER_TABLEAUX, vec![card(Rank::Ace, Suit::Spades)]);
+    assert!(is_invalid_card(area.accepts_cards(&other_ace).unwrap_err()));
+}
+
+#[test]
+fn foundation_accepts_next_rank_of_its_suit() {
+    let area = foundation(vec![card(Rank::Ace, Suit::Hearts)]);
+
+    let two = held(AreaId::Talon, vec![card(Rank::Two, Suit::Hearts)]);
+    assert!(area.accepts_cards(&two).is_ok());
+
+    let three = held(AreaId::Talon, vec![card(Rank::Three, Suit::Hearts)]);
+    assert!(is_invalid_card(area.accepts_cards(&three).unwrap_err()));
+
+    let other_two = held(AreaId::Talon, vec![card(Rank::Two, Suit::Diamonds)]);
+    assert!(is_invalid_card(area.accepts_cards(&other_two).unwrap_err()));
+}
+
+#[test]
+fn foundation_rejects_more_than_one_card() {
+    let area = foundation(vec![]);
+    let held = held(
+        OTHER_TABLEAUX,
+        vec![card(Rank::Ace, Suit::Hearts), card(Rank::Two, Suit::Hearts)],
+    );
+
+    assert!(matches!(
+        area.accepts_cards(&held).unwrap_err(),
+        Error::TooManyCards { .. }
+    ));
+}
+
+#[test]
+fn foundation_takes_back_own_cards() {
+    let area = foundation(vec![card(Rank::Ace, Suit::Hearts)]);
+    let held = held(AreaId::Foundation(0), vec![card(Rank::Two, Suit::Hearts)]);
+
+    assert!(area.accepts_cards(&held).is_ok());
+}
+
+#[test]
+fn stock_accepts_only_stock_and_talon_cards() {
+    let area = UnselectedStock::create(vec![], &GameSettings::default());
+    let cards = vec![card(Rank::Five, Suit::Clubs)];
+
+    for &source in &[AreaId::Stock, AreaId::Talon] {
+        assert!(area.accepts_cards(&held(source, cards.clone())).is_ok());
+    }
+
+    for &source in &[AreaId::Foundation(0), AreaId::Tableaux(0)] {
+        assert!(matches!(
+            area.accepts_cards(&held(source, cards.clone()))
+                .unwrap_err(),
+            Error::NotSupported { .. }
+        ));
+    }
+}
+
+#[test]
+fn talon_accepts_only_stock_and_talon_cards() {
+    let area = UnselectedTalon::create(vec![], 0);
+    let cards = vec![card(Rank::Five, Suit::Clubs)];
+
+    for &source in &[AreaId::Stock, AreaId::Talon] {
+        assert!(area.accepts_cards(&held(source, cards.clone())).is_ok());
+    }
+
+    for &source in &[AreaId::Foundation(0), AreaId::Tableaux(0)] {
+        assert!(matches!(
+            area.accepts_cards(&held(source, cards.clone()))
+                .unwrap_err(),
+            Error::NotSupported { .. }
+        ));
+    }
+}
+
+#[test]
+fn tableaux_rejection_names_the_cards() {
+    let area = tableaux(vec![card(Rank::Ten, Suit::Clubs)], 1);
+    let held = held(OTHER_TABLEAUX, vec![card(Rank::Queen, Suit::Diamonds)]);
+
+    let error = area.accepts_cards(&held).unwrap_err();
+    assert_eq!(
+        error.rejection_message(area.id()).as_deref(),
+        Some("Q♦ can't go on 10♣")
+    );
+}
+
+#[test]
+fn foundation_rejection_names_its_suit() {
+    let area = foundation(vec![]);
+    let held = held(OTHER_TABLEAUX, vec![card(Rank::Ace, Suit::Clubs)]);
+
+    let error = area.accepts_cards(&held).unwrap_err();
+    assert_eq!(
+        error.rejection_message(area.id()).as_deref(),
+        Some("Foundation only accepts ♥")
+    );
+}
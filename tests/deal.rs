@@ -0,0 +1,106 @@
+//! Dealing the tableaux for every number of piles the settings allow, and dealing the same game
+//! from the same seed.
+
+use klondike_lib::model::{
+    dealer::create_dealer,
+    rules::rules_for,
+    settings::{
+        DealerMode, Error, GameSettings, Variant, DOUBLE_DECK_TABLEAUX_LEN_RANGE,
+        TABLEAUX_LEN_RANGE,
+    },
+    AreaId, Card, Game, Rank, Suit,
+};
+
+fn deck(deck_count: u8) -> Vec<Card> {
+    (0..deck_count)
+        .flat_map(|_| Suit::values())
+        .flat_map(|suit| Rank::values().map(move |rank| Card { suit, rank }))
+        .collect()
+}
+
+fn assert_deals(deck_count: u8, tableaux_len: u8) {
+    let settings = GameSettings {
+        variant: Variant::Klondike,
+        deck_count,
+        tableaux_len,
+        ..GameSettings::default()
+    };
+    settings.validate().unwrap();
+
+    let mut deck = deck(deck_count);
+    let piles = rules_for(Variant::Klondike).deal_tableaux(&mut deck, &settings);
+
+    let lens = piles
+        .iter()
+        .map(|pile| pile.cards.len())
+        .collect::<Vec<_>>();
+    let expected = (1..=usize::from(tableaux_len)).collect::<Vec<_>>();
+    assert_eq!(lens, expected, "{} deck(s)", deck_count);
+    assert!(piles.iter().all(|pile| pile.spread_len == 1));
+    assert!(
+        !deck.is_empty(),
+        "{} deck(s), {} piles",
+        deck_count,
+        tableaux_len
+    );
+}
+
+#[test]
+fn every_tableaux_len_deals_full_piles_and_a_stock() {
+    for tableaux_len in TABLEAUX_LEN_RANGE.clone() {
+        assert_deals(1, tableaux_len);
+    }
+    for tableaux_len in DOUBLE_DECK_TABLEAUX_LEN_RANGE.clone() {
+        assert_deals(2, tableaux_len);
+    }
+}
+
+#[test]
+fn ten_piles_need_two_decks() {
+    let settings = GameSettings {
+        tableaux_len: 10,
+        ..GameSettings::default()
+    };
+    assert!(matches!(
+        settings.validate(),
+        Err(Error::InvalidTableauxLen { len: 10, .. })
+    ));
+}
+
+fn cards(game: &Game, area_id: AreaId) -> String {
+    let stack = game.stack(area_id).unwrap();
+    stack
+        .cards
+        .iter()
+        .map(Card::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Saved and shared seeds have to keep dealing the same game, so this pins the deal for one seed.
+/// If it fails, the shuffle has changed, and every seed out there now deals something else.
+#[test]
+fn seed_deals_the_same_game() {
+    let settings = GameSettings::default();
+    let game = create_dealer(DealerMode::Seeded(1)).deal_game(Some(1), &settings);
+
+    let tableaux = (0..7)
+        .map(|index| cards(&game, AreaId::Tableaux(index)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tableaux,
+        vec![
+            "J♣",
+            "K♦ A♠",
+            "10♦ 2♦ 3♥",
+            "2♥ 6♣ K♠ J♦",
+            "Q♦ A♥ 7♥ J♥ K♥",
+            "10♣ 6♥ 2♠ A♣ 2♣ 4♥",
+            "4♠ Q♠ 5♠ 3♠ 10♥ 7♠ J♠",
+        ]
+    );
+    assert_eq!(
+        cards(&game, AreaId::Stock),
+        "7♦ 3♣ 9♣ 4♣ 5♥ 3♦ 4♦ 10♠ 6♠ 6♦ 7♣ Q♥ 9♠ K♣ 8♦ 5♣ 8♥ 5♦ 8♣ A♦ 8♠ 9♦ 9♥ Q♣"
+    );
+}
@@ -0,0 +1,27 @@
+//! Keeping time while a game is played.
+
+use std::{thread, time::Duration};
+
+use klondike_lib::model::{
+    dealer::create_dealer,
+    settings::{DealerMode, GameSettings},
+};
+
+#[test]
+fn clock_stops_while_paused() {
+    let settings = GameSettings::default();
+    let mut game = create_dealer(DealerMode::InOrder).deal_game(None, &settings);
+
+    game.pause();
+    let paused_at = game.elapsed();
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(game.elapsed(), paused_at);
+
+    // Pausing twice mustn't lose the time already played.
+    game.pause();
+    assert_eq!(game.elapsed(), paused_at);
+
+    game.resume();
+    thread::sleep(Duration::from_millis(20));
+    assert!(game.elapsed() >= paused_at + Duration::from_millis(20));
+}
@@ -0,0 +1,228 @@
+//! Undoing and redoing each kind of move.
+
+use klondike_lib::model::{
+    area::{
+        foundation::UnselectedFoundation, stock::UnselectedStock, tableaux::UnselectedTableaux,
+        talon::UnselectedTalon, AreaSnapshot, UnselectedArea,
+    },
+    area_list::AreaList,
+    history::{History, Move},
+    settings::GameSettings,
+    AreaId, Card, Rank, Suit,
+};
+
+fn card(rank: Rank, suit: Suit) -> Card {
+    Card { rank, suit }
+}
+
+/// A small layout with a couple of cards in the stock, an empty talon, empty foundations, and two
+/// tableaux: a lone nine of spades, and a face-down five of clubs under an eight of hearts.
+fn area_list() -> AreaList {
+    let settings = GameSettings {
+        draw_from_stock_len: 1,
+        ..GameSettings::default()
+    };
+
+    let mut areas: Vec<Box<dyn UnselectedArea>> = vec![
+        UnselectedStock::create(
+            vec![
+                card(Rank::Two, Suit::Hearts),
+                card(Rank::Three, Suit::Clubs),
+            ],
+            &settings,
+        ),
+        UnselectedTalon::create(vec![], 0),
+    ];
+    areas.extend(
+        Suit::values().enumerate().map(|(index, suit)| {
+            UnselectedFoundation::create(index as u8, suit, vec![], &settings)
+        }),
+    );
+    areas.push(UnselectedTableaux::create(
+        0,
+        1,
+        vec![card(Rank::Nine, Suit::Spades)],
+        &settings,
+    ));
+    areas.push(UnselectedTableaux::create(
+        1,
+        1,
+        vec![
+            card(Rank::Five, Suit::Clubs),
+            card(Rank::Eight, Suit::Hearts),
+        ],
+        &settings,
+    ));
+
+    AreaList::new(areas).unwrap()
+}
+
+/// The cards in every area, leaving out the selection and the history itself. Undo moves the
+/// selection to wherever the cards changed, so it won't always go back to where it was.
+fn state(areas: &AreaList) -> Vec<AreaSnapshot> {
+    areas.snapshot().areas
+}
+
+fn activate(areas: &mut AreaList, area_id: AreaId) {
+    areas.move_selection(area_id).unwrap();
+    areas.activate_selected().unwrap();
+}
+
+fn transfer(areas: &mut AreaList) {
+    activate(areas, AreaId::Tableaux(1));
+    activate(areas, AreaId::Tableaux(0));
+}
+
+/// Applies a move, then checks that undoing it brings back the state from before, and that redoing
+/// it brings back the state from after.
+fn assert_round_trip(areas: &mut AreaList, apply: impl FnOnce(&mut AreaList)) -> Move {
+    let before = state(areas);
+    apply(areas);
+    let after = state(areas);
+    let applied = areas.history().last().cloned().unwrap();
+
+    areas.undo().unwrap();
+    assert_eq!(state(areas), before);
+    assert!(areas.history().can_redo());
+
+    areas.redo().unwrap();
+    assert_eq!(state(areas), after);
+    assert_eq!(areas.history().last(), Some(&applied));
+
+    applied
+}
+
+#[test]
+fn transfer_round_trip() {
+    let mut areas = area_list();
+
+    let applied = assert_round_trip(&mut areas, transfer);
+    assert_eq!(
+        applied,
+        Move::Transfer {
+            source: AreaId::Tableaux(1),
+            target: AreaId::Tableaux(0),
+            cards: vec![card(Rank::Eight, Suit::Hearts)],
+            source_spread_len: 1,
+        }
+    );
+}
+
+#[test]
+fn draw_round_trip() {
+    let mut areas = area_list();
+
+    let applied = assert_round_trip(&mut areas, |areas| activate(areas, AreaId::Stock));
+    assert_eq!(
+        applied,
+        Move::Draw {
+            len: 1,
+            talon_spread_len: 0,
+        }
+    );
+}
+
+#[test]
+fn restock_round_trip() {
+    let mut areas = area_list();
+    activate(&mut areas, AreaId::Stock);
+    activate(&mut areas, AreaId::Stock);
+
+    let applied = assert_round_trip(&mut areas, |areas| activate(areas, AreaId::Stock));
+    assert_eq!(
+        applied,
+        Move::Restock {
+            len: 2,
+            talon_spread_len: 1,
+        }
+    );
+    assert_eq!(areas.restock_count(), 1);
+}
+
+#[test]
+fn reveal_round_trip() {
+    let mut areas = area_list();
+    transfer(&mut areas);
+
+    let applied = assert_round_trip(&mut areas, |areas| activate(areas, AreaId::Tableaux(1)));
+    assert_eq!(
+        applied,
+        Move::Reveal {
+            area_id: AreaId::Tableaux(1)
+        }
+    );
+}
+
+#[test]
+fn undo_twice_and_redo_twice() {
+    let mut areas = area_list();
+    let start = state(&areas);
+    transfer(&mut areas);
+    activate(&mut areas, AreaId::Tableaux(1));
+    let end = state(&areas);
+
+    areas.undo().unwrap();
+    areas.undo().unwrap();
+    assert_eq!(state(&areas), start);
+    assert!(areas.undo().is_err());
+
+    areas.redo().unwrap();
+    areas.redo().unwrap();
+    assert_eq!(state(&areas), end);
+    assert!(areas.redo().is_err());
+}
+
+#[test]
+fn new_move_clears_redo() {
+    let mut areas = area_list();
+    transfer(&mut areas);
+    areas.undo().unwrap();
+    assert!(areas.history().can_redo());
+
+    activate(&mut areas, AreaId::Stock);
+    assert!(!areas.history().can_redo());
+    assert!(areas.redo().is_err());
+}
+
+#[test]
+fn record_clears_redo() {
+    let mut history = History::new();
+    let reveal = Move::Reveal {
+        area_id: AreaId::Tableaux(0),
+    };
+
+    history.record(reveal.clone());
+    assert_eq!(history.undo(), Some(reveal.clone()));
+    assert!(history.can_redo());
+
+    history.record(reveal);
+    assert!(!history.can_redo());
+    assert_eq!(history.redo(), None);
+}
+
+#[test]
+fn hiding_cards_shrinks_selection() {
+    let settings = GameSettings::default();
+    let cards = vec![
+        card(Rank::Ten, Suit::Clubs),
+        card(Rank::Nine, Suit::Hearts),
+        card(Rank::Eight, Suit::Spades),
+    ];
+    let mut area = match UnselectedTableaux::create(0, 3, cards, &settings)
+        .select()
+        .into_result()
+    {
+        Ok(area) => area,
+        Err(_) => panic!("Unable to select tableaux"),
+    };
+    area.select_more().unwrap();
+    area.select_more().unwrap();
+
+    area.set_spread_len(1);
+    let selection = area.as_stack().details.selection.unwrap();
+    assert_eq!(selection.len, 1);
+
+    area.set_spread_len(0);
+    let selection = area.as_stack().details.selection.unwrap();
+    assert_eq!(selection.len, 1);
+}
@@ -0,0 +1,144 @@
+//! Building keymaps from the presets and the key settings.
+
+use klondike_lib::model::{
+    game::Action,
+    keys::{parse_key, Command, Error, KeyNames, KeyPreset, KeySettings},
+    AreaId,
+};
+use termion::event::Key;
+
+fn settings(preset: KeyPreset, playing: &[(&str, &[&str])]) -> KeySettings {
+    KeySettings {
+        preset,
+        playing: playing
+            .iter()
+            .map(|(command, keys)| {
+                let keys = keys.iter().map(|key| key.to_string()).collect();
+                (command.to_string(), KeyNames::Many(keys))
+            })
+            .collect(),
+        ..KeySettings::default()
+    }
+}
+
+#[test]
+fn vim_preset_moves_with_letters_and_arrows() {
+    let keymap = settings(KeyPreset::Vim, &[]).playing_keymap().unwrap();
+
+    for &key in &[Key::Char('h'), Key::Left] {
+        assert_eq!(
+            keymap.command_for(key),
+            Some(Command::Action(Action::MoveLeft))
+        );
+    }
+    assert_eq!(
+        keymap.command_for(Key::Char('3')),
+        Some(Command::Action(Action::MoveTo(AreaId::Tableaux(2))))
+    );
+}
+
+#[test]
+fn tenth_tableaux_is_zero() {
+    let keymap = settings(KeyPreset::Arrows, &[]).playing_keymap().unwrap();
+
+    assert_eq!(
+        keymap.command_for(Key::Char('0')),
+        Some(Command::Action(Action::MoveTo(AreaId::Tableaux(9))))
+    );
+}
+
+#[test]
+fn arrows_preset_leaves_letters_free() {
+    let keymap = settings(KeyPreset::Arrows, &[]).playing_keymap().unwrap();
+
+    assert_eq!(keymap.command_for(Key::Char('h')), None);
+    assert_eq!(
+        keymap.command_for(Key::Left),
+        Some(Command::Action(Action::MoveLeft))
+    );
+}
+
+#[test]
+fn numpad_preset_moves_with_digits() {
+    let keymap = settings(KeyPreset::Numpad, &[]).playing_keymap().unwrap();
+
+    assert_eq!(
+        keymap.command_for(Key::Char('8')),
+        Some(Command::Action(Action::SelectMore))
+    );
+    assert_eq!(
+        keymap.command_for(Key::Char('5')),
+        Some(Command::Action(Action::Activate))
+    );
+}
+
+#[test]
+fn settings_replace_preset_keys() {
+    let keymap = settings(KeyPreset::Vim, &[("undo", &["ctrl-z", "U"])])
+        .playing_keymap()
+        .unwrap();
+
+    assert_eq!(keymap.command_for(Key::Char('u')), None);
+    assert_eq!(
+        keymap.keys_for(Command::Action(Action::Undo)),
+        &[Key::Ctrl('z'), Key::Char('U')]
+    );
+}
+
+#[test]
+fn conflicting_keys_are_rejected() {
+    let error = settings(KeyPreset::Vim, &[("hint", &["h"])])
+        .playing_keymap()
+        .unwrap_err();
+
+    assert!(matches!(error, Error::ConflictingBindings { .. }));
+    assert_eq!(
+        error.to_string(),
+        "Key h is bound to both move-left and hint"
+    );
+}
+
+#[test]
+fn unknown_names_are_rejected() {
+    let error = settings(KeyPreset::Vim, &[("teleport", &["x"])])
+        .playing_keymap()
+        .unwrap_err();
+    assert!(matches!(error, Error::InvalidCommand { .. }));
+
+    let error = settings(KeyPreset::Vim, &[("hint", &["hyper-x"])])
+        .playing_keymap()
+        .unwrap_err();
+    assert!(matches!(error, Error::InvalidKeyName { .. }));
+}
+
+#[test]
+fn settings_bind_keys_to_commands() {
+    let mut key_settings = settings(KeyPreset::Vim, &[]);
+    for &(key, command) in &[("ctrl-z", "undo"), ("shift-z", "redo")] {
+        key_settings
+            .playing
+            .insert(key.to_string(), KeyNames::One(command.to_string()));
+    }
+    let keymap = key_settings.playing_keymap().unwrap();
+
+    assert_eq!(
+        keymap.keys_for(Command::Action(Action::Undo)),
+        &[Key::Char('u'), Key::Ctrl('z')]
+    );
+    assert_eq!(
+        keymap.command_for(Key::Char('Z')),
+        Some(Command::Action(Action::Redo))
+    );
+}
+
+#[test]
+fn key_names_parse() {
+    assert_eq!(parse_key("H").unwrap(), Key::Char('H'));
+    assert_eq!(parse_key("Enter").unwrap(), Key::Char('\n'));
+    assert_eq!(parse_key("ctrl-R").unwrap(), Key::Ctrl('r'));
+    assert_eq!(parse_key("alt-X").unwrap(), Key::Alt('X'));
+    assert_eq!(parse_key("F12").unwrap(), Key::F(12));
+    assert_eq!(parse_key("shift-h").unwrap(), Key::Char('H'));
+    assert!(parse_key("shift-1").is_err());
+    assert!(parse_key("f13").is_err());
+}
@@ -0,0 +1,85 @@
+//! Searching deals for a win, and giving up when the search runs over its limits.
+
+use std::time::Duration;
+
+use klondike_lib::model::{
+    area::{
+        foundation::UnselectedFoundation, stock::UnselectedStock, tableaux::UnselectedTableaux,
+        talon::UnselectedTalon, UnselectedArea,
+    },
+    area_list::AreaList,
+    dealer::create_dealer,
+    settings::{DealerMode, GameSettings},
+    solver::{Limits, Solution, Solver},
+    Card, Game, Rank, Suit,
+};
+
+fn settings() -> GameSettings {
+    GameSettings {
+        take_from_foundation: false,
+        ..GameSettings::default()
+    }
+}
+
+/// Every suit but spades is complete on its foundation. The spades are stacked in a single
+/// tableaux, ace at the bottom and king on top, with only the king face up, and a second tableaux
+/// is empty. The king can move to the empty tableaux, but nothing can go on it, and nothing can go
+/// on the queen underneath it.
+fn blocked_spades() -> Game {
+    let settings = settings();
+
+    let mut areas: Vec<Box<dyn UnselectedArea>> = vec![
+        UnselectedStock::create(vec![], &settings),
+        UnselectedTalon::create(vec![], 0),
+    ];
+    areas.extend(Suit::values().enumerate().map(|(index, suit)| {
+        let cards = if suit == Suit::Spades {
+            vec![]
+        } else {
+            Rank::values().map(|rank| Card { suit, rank }).collect()
+        };
+        UnselectedFoundation::create(index as u8, suit, cards, &settings)
+    }));
+
+    let spades = Rank::values()
+        .map(|rank| Card {
+            suit: Suit::Spades,
+            rank,
+        })
+        .collect();
+    areas.push(UnselectedTableaux::create(0, 1, spades, &settings));
+    areas.push(UnselectedTableaux::create(1, 0, vec![], &settings));
+
+    Game::new(AreaList::new(areas).unwrap(), None, &settings)
+}
+
+#[test]
+fn won_deal_is_solvable() {
+    let settings = settings();
+    let game = create_dealer(DealerMode::AutoWin).deal_game(None, &settings);
+
+    let solution = Solver::new(&settings).solve_game(&game).unwrap();
+    assert_eq!(solution, Solution::Solvable(vec![]));
+}
+
+#[test]
+fn blocked_deal_is_unsolvable() {
+    let settings = settings();
+
+    let solution = Solver::new(&settings)
+        .solve_game(&blocked_spades())
+        .unwrap();
+    assert_eq!(solution, Solution::Unsolvable);
+}
+
+#[test]
+fn search_gives_up_at_node_limit() {
+    let settings = settings();
+    let game = create_dealer(DealerMode::Seeded(1)).deal_game(Some(1), &settings);
+
+    let solver = Solver::new(&settings).limits(Limits {
+        max_nodes: 1,
+        max_duration: Duration::from_secs(60),
+    });
+    assert_eq!(solver.solve_game(&game).unwrap(), Solution::Unknown);
+}
@@ -0,0 +1,147 @@
+//! Summing up the recorded games, and exporting them.
+
+use std::time::Duration;
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use klondike_lib::model::{
+    settings::DealerMode,
+    stats::{export_records, ExportFormat, GameRecord, GameResult, RecordFilter, Statistics},
+};
+
+fn record(result: GameResult, time_secs: u64, foundation_len: usize) -> GameRecord {
+    GameRecord {
+        date: Utc.ymd(2020, 1, 1).and_hms(12, 0, 0),
+        seed: Some(1),
+        dealer: DealerMode::Random,
+        draw_from_stock_len: 3,
+        result,
+        moves: 100,
+        time: Duration::from_secs(time_secs),
+        score: 0,
+        foundation_len,
+    }
+}
+
+fn won(time_secs: u64) -> GameRecord {
+    record(GameResult::Won, time_secs, 52)
+}
+
+fn lost(foundation_len: usize) -> GameRecord {
+    record(GameResult::Lost, 600, foundation_len)
+}
+
+fn statistics(records: Vec<GameRecord>) -> Statistics {
+    Statistics { records }
+}
+
+#[test]
+fn empty_statistics() {
+    let statistics = Statistics::default();
+
+    assert_eq!(statistics.win_rate(), None);
+    assert_eq!(statistics.current_streak(), 0);
+    assert_eq!(statistics.best_streak(), 0);
+    assert_eq!(statistics.fastest_win(), None);
+    assert_eq!(statistics.foundation_histogram(10), vec![(0, 0)]);
+}
+
+#[test]
+fn win_rate_counts_every_game() {
+    let statistics = statistics(vec![won(300), lost(10), lost(20), won(200)]);
+
+    assert_eq!(statistics.win_rate(), Some(0.5));
+    assert_eq!(statistics.fastest_win(), Some(Duration::from_secs(200)));
+}
+
+#[test]
+fn streaks() {
+    let statistics = statistics(vec![
+        won(300),
+        won(300),
+        won(300),
+        lost(10),
+        won(300),
+        won(300),
+    ]);
+
+    assert_eq!(statistics.current_streak(), 2);
+    assert_eq!(statistics.best_streak(), 3);
+}
+
+#[test]
+fn trailing_loss_ends_current_streak() {
+    let statistics = statistics(vec![won(300), won(300), lost(10)]);
+
+    assert_eq!(statistics.current_streak(), 0);
+    assert_eq!(statistics.best_streak(), 2);
+}
+
+#[test]
+fn foundation_histogram_buckets() {
+    let statistics = statistics(vec![lost(0), lost(9), lost(10), lost(25), won(300)]);
+
+    assert_eq!(
+        statistics.foundation_histogram(10),
+        vec![(0, 2), (10, 1), (20, 1), (30, 0), (40, 0), (50, 1)]
+    );
+}
+
+fn dealt_on(day: u32, dealer: DealerMode) -> GameRecord {
+    GameRecord {
+        date: Utc.ymd(2020, 1, day).and_hms(12, 0, 0),
+        dealer,
+        ..won(300)
+    }
+}
+
+fn export_csv(statistics: &Statistics, filter: &RecordFilter) -> Vec<String> {
+    let mut output = vec![];
+    export_records(statistics, filter, ExportFormat::Csv, &mut output).unwrap();
+
+    String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn csv_export_has_header_and_columns() {
+    let statistics = statistics(vec![dealt_on(2, DealerMode::Seeded(7))]);
+
+    assert_eq!(
+        export_csv(&statistics, &RecordFilter::default()),
+        vec![
+            "date,seed,dealer,draw_count,result,moves,time,score,foundation_len",
+            "2020-01-02T12:00:00Z,1,Seeded,3,Won,100,300,0,52",
+        ]
+    );
+}
+
+#[test]
+fn export_filters_by_date_and_dealer() {
+    let statistics = statistics(vec![
+        dealt_on(1, DealerMode::Random),
+        dealt_on(2, DealerMode::Random),
+        dealt_on(3, DealerMode::Seeded(7)),
+        dealt_on(4, DealerMode::Random),
+    ]);
+
+    let filter = RecordFilter {
+        since: Some(NaiveDate::from_ymd(2020, 1, 2)),
+        until: Some(NaiveDate::from_ymd(2020, 1, 3)),
+        dealer: None,
+    };
+    let lines = export_csv(&statistics, &filter);
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].starts_with("2020-01-02"));
+    assert!(lines[2].starts_with("2020-01-03"));
+
+    let filter = RecordFilter {
+        dealer: Some("random".to_string()),
+        ..RecordFilter::default()
+    };
+    let lines = export_csv(&statistics, &filter);
+    assert_eq!(lines.len(), 4);
+    assert!(lines[1..].iter().all(|line| line.contains(",Random,")));
+}
@@ -0,0 +1,56 @@
+//! What activating the stock does under each variant's rules.
+
+use klondike_lib::model::{
+    area::{stock::UnselectedStock, Action, Error, SelectedArea},
+    settings::{GameSettings, Variant},
+    Card, Rank, Suit,
+};
+
+fn selected_stock(cards: Vec<Card>, settings: &GameSettings) -> Box<dyn SelectedArea> {
+    match UnselectedStock::create(cards, settings)
+        .select()
+        .into_result()
+    {
+        Ok(stock) => stock,
+        Err(_) => panic!("Unable to select stock"),
+    }
+}
+
+#[test]
+fn klondike_draws_then_restocks() {
+    let settings = GameSettings::default();
+    let card = Card {
+        rank: Rank::Ace,
+        suit: Suit::Spades,
+    };
+
+    let mut stock = selected_stock(vec![card], &settings);
+    assert_eq!(stock.activate().unwrap(), Some(Action::Draw(3)));
+
+    let mut stock = selected_stock(vec![], &settings);
+    assert_eq!(stock.activate().unwrap(), Some(Action::Restock));
+}
+
+#[test]
+fn klondike_stops_restocking_after_last_pass() {
+    let settings = GameSettings {
+        max_stock_passes: Some(1),
+        ..GameSettings::default()
+    };
+
+    let mut stock = selected_stock(vec![], &settings);
+    let error = stock.activate().unwrap_err();
+    assert!(matches!(error, Error::NoPassesRemain { max_passes: 1 }));
+}
+
+#[test]
+fn yukon_has_no_stock() {
+    let settings = GameSettings {
+        variant: Variant::Yukon,
+        ..GameSettings::default()
+    };
+
+    let mut stock = selected_stock(vec![], &settings);
+    let error = stock.activate().unwrap_err();
+    assert!(matches!(error, Error::NothingToSelect { .. }));
+}
@@ -0,0 +1,70 @@
+//! Reading theme colours and drawing with the built-in themes.
+
+use std::collections::HashSet;
+
+use klondike_lib::{
+    display::theme::{Error, Theme, ThemeColor, ThemeStyle, BUILTIN_THEME_NAMES},
+    model::Suit,
+};
+
+fn theme(name: &str, color: bool, unicode: bool) -> Theme {
+    Theme {
+        color,
+        unicode,
+        style: ThemeStyle::builtin(name).unwrap(),
+    }
+}
+
+#[test]
+fn colors_parse() {
+    assert_eq!(
+        "light-blue".parse::<ThemeColor>().unwrap(),
+        ThemeColor::LightBlue
+    );
+    assert_eq!("Red".parse::<ThemeColor>().unwrap(), ThemeColor::Red);
+    assert_eq!("208".parse::<ThemeColor>().unwrap(), ThemeColor::Ansi(208));
+    assert_eq!(
+        "#FF8000".parse::<ThemeColor>().unwrap(),
+        ThemeColor::Rgb(255, 128, 0)
+    );
+
+    for name in &["purple", "256", "#ff80", "#gg8000"] {
+        let error = name.parse::<ThemeColor>().unwrap_err();
+        assert!(matches!(error, Error::InvalidColor { .. }), "{}", name);
+    }
+}
+
+#[test]
+fn every_builtin_theme_exists() {
+    for name in BUILTIN_THEME_NAMES {
+        assert!(ThemeStyle::builtin(name).is_some(), "{}", name);
+    }
+    assert!(ThemeStyle::builtin("ocean").is_none());
+}
+
+#[test]
+fn four_color_deck_colors_every_suit() {
+    let theme = theme("four-color", true, true);
+    let colors = Suit::values()
+        .map(|suit| theme.suit_color(suit))
+        .collect::<HashSet<_>>();
+
+    assert_eq!(colors.len(), 4);
+}
+
+#[test]
+fn suits_without_color_or_unicode() {
+    let monochrome = theme("classic", false, true);
+    assert_eq!(monochrome.suit_symbol(Suit::Hearts), "♡");
+    assert_eq!(monochrome.suit_symbol(Suit::Spades), "♠");
+
+    let ascii = theme("classic", true, false);
+    assert_eq!(ascii.suit_symbol(Suit::Diamonds), "D");
+    assert_eq!(ascii.suits_in("Q♦ can't go on K♠"), "QD can't go on KS");
+}
+
+#[test]
+fn card_back_falls_back_to_ascii() {
+    assert_eq!(theme("felt", true, true).card_back(), "▚▞▚▞");
+    assert_eq!(theme("felt", true, false).card_back(), "####");
+}